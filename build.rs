@@ -9,6 +9,7 @@ fn parse_str(values: &[&str]) -> Vec<f64> {
     values.iter().map(|s| f64::from_str(s).unwrap()).collect()
 }
 
+#[cfg(feature = "wmm")]
 #[inline]
 fn vec_to_out<P: AsRef<Path>>(v: Vec<Vec<f64>>, file: P) {
     let out_dir = env::var("OUT_DIR").unwrap();
@@ -20,8 +21,21 @@ fn vec_to_out<P: AsRef<Path>>(v: Vec<Vec<f64>>, file: P) {
         .unwrap();
 }
 
+#[cfg(feature = "igrf")]
+#[inline]
+fn vec_to_slice_out<P: AsRef<Path>>(v: Vec<Vec<f64>>, file: P) {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+
+    let rows: Vec<String> = v.iter().map(|r| format!("&{:?}", r)).collect();
+    File::create(out_dir.join(file))
+        .unwrap()
+        .write_all(format!("&[{}]", rows.join(", ")).as_bytes())
+        .unwrap();
+}
+
 #[cfg(feature = "wmm")]
-fn parse_wmm<P: AsRef<Path>>(p: P) {
+fn parse_wmm<P: AsRef<Path>>(p: P, out: &str) {
     let content = fs::read_to_string(p).unwrap();
     let constant = content
         .lines()
@@ -31,12 +45,15 @@ fn parse_wmm<P: AsRef<Path>>(p: P) {
         })
         .collect();
 
-    vec_to_out(constant, "WMM_COF");
+    vec_to_out(constant, out);
 }
 
 #[cfg(not(feature = "wmm"))]
-fn parse_wmm<P: AsRef<Path>>(_p: P) {}
+fn parse_wmm<P: AsRef<Path>>(_p: P, _out: &str) {}
 
+/// Reads the layout of the official `igrfNNcoeffs.txt`: `#` comments, a `c/s` line, a
+/// `g/h n m` line naming the epochs and the predictive SV column, then one row per
+/// coefficient.
 #[cfg(feature = "igrf")]
 fn parse_igrf<P: AsRef<Path>>(p: P) {
     let content = fs::read_to_string(p).unwrap();
@@ -49,8 +66,15 @@ fn parse_igrf<P: AsRef<Path>>(p: P) {
     let mut constant_h = Vec::new();
 
     for values_str in constant {
-        match values_str[0] {
-            "g" => {
+        match values_str.first().copied() {
+            Some("g/h") => {
+                // IGRF::new indexes epochs as 1900 + 5 i.
+                let epochs = parse_str(&values_str[3..values_str.len() - 1]);
+                for (i, epoch) in epochs.iter().enumerate() {
+                    assert_eq!(*epoch, 1900.0 + 5.0 * i as f64, "unexpected IGRF epoch");
+                }
+            }
+            Some("g") => {
                 let values = &values_str[3..];
                 constant_g.push(parse_str(values));
 
@@ -58,7 +82,7 @@ fn parse_igrf<P: AsRef<Path>>(p: P) {
                     constant_h.push(vec![0_f64; values.len()])
                 }
             }
-            "h" => {
+            Some("h") => {
                 let values = &values_str[3..];
                 constant_h.push(parse_str(values));
             }
@@ -66,15 +90,56 @@ fn parse_igrf<P: AsRef<Path>>(p: P) {
         }
     }
 
-    vec_to_out(constant_g, "IGRF_COF_G");
-    vec_to_out(constant_h, "IGRF_COF_H");
+    vec_to_slice_out(constant_g, "IGRF_COF_G");
+    vec_to_slice_out(constant_h, "IGRF_COF_H");
 }
 
 #[cfg(not(feature = "igrf"))]
 fn parse_igrf<P: AsRef<Path>>(_p: P) {}
 
+/// Embeds the 1° EGM96 grid of `data/EGM96.I16`: undulations in centimetres as
+/// little-endian `i16`, row by row from 90° N to 90° S, each row from 0° to 360° E.
+/// `cargo run --example egm96` produces it from NGA's `WW15MGH.GRD`.
+#[cfg(feature = "geoid")]
+fn parse_geoid<P: AsRef<Path>>(p: P) {
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let out_dir = Path::new(&out_dir);
+
+    let p = p.as_ref();
+    let bytes = fs::read(p).unwrap_or_else(|e| {
+        panic!(
+            "the geoid feature needs {}: {}\n\
+             generate it with `cargo run --example egm96 -- WW15MGH.GRD {}`",
+            p.display(),
+            e,
+            p.display()
+        )
+    });
+    assert_eq!(
+        bytes.len(),
+        2 * 181 * 361,
+        "{} does not hold a 1° global grid",
+        p.display()
+    );
+    let inner: Vec<i16> = bytes
+        .chunks_exact(2)
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    let constant = format!("([-90.0, 90.0, 0.0, 360.0, 1.0, 1.0], &{:?})", inner);
+
+    File::create(out_dir.join("EGM96_GRD"))
+        .unwrap()
+        .write_all(constant.as_bytes())
+        .unwrap();
+}
+
+#[cfg(not(feature = "geoid"))]
+fn parse_geoid<P: AsRef<Path>>(_p: P) {}
+
 fn main() {
     let data_dir = Path::new("data");
-    parse_wmm(data_dir.join("WMM.COF"));
-    parse_igrf(data_dir.join("IGRF.COF"));
+    parse_wmm(data_dir.join("WMM2020.COF"), "WMM2020_COF");
+    parse_wmm(data_dir.join("WMM2025.COF"), "WMM2025_COF");
+    parse_igrf(data_dir.join("igrf14coeffs.txt"));
+    parse_geoid(data_dir.join("EGM96.I16"));
 }
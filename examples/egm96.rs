@@ -0,0 +1,80 @@
+//! Downsamples NGA's `WW15MGH.GRD` EGM96 grid to the 1° grid embedded by the `geoid`
+//! feature:
+//!
+//! ```sh
+//! cargo run --example egm96 -- WW15MGH.GRD data/EGM96.I16
+//! ```
+//!
+//! The output holds undulations in centimetres as little-endian `i16`, row by row from
+//! 90° N to 90° S, each row from 0° to 360° E including both edges.
+
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let [input, output] = &args[..] else {
+        eprintln!("Usage: egm96 <WW15MGH.GRD> <output>");
+        return ExitCode::from(2);
+    };
+
+    match convert(input, output) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("egm96: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn convert(input: &str, output: &str) -> Result<(), String> {
+    let content = fs::read_to_string(input).map_err(|e| format!("{}: {}", input, e))?;
+    let values = content
+        .split_whitespace()
+        .map(|v| v.parse().map_err(|_| format!("invalid number '{}'", v)))
+        .collect::<Result<Vec<f64>, _>>()?;
+
+    let Some((&[south, north, west, east, d_lat, d_lon], values)) = values.split_first_chunk()
+    else {
+        return Err("missing header".to_string());
+    };
+
+    if (south, north, west, east) != (-90.0, 90.0, 0.0, 360.0) {
+        return Err(format!(
+            "expected a global grid, found {} to {} by {} to {}",
+            south, north, west, east
+        ));
+    }
+
+    // Steps of the input grid per degree, which must be whole.
+    let per_degree = |d: f64| {
+        let k = (1.0 / d).round();
+        (k >= 1.0 && (k * d - 1.0).abs() < 1e-9)
+            .then_some(k as usize)
+            .ok_or(format!("spacing {} does not divide a degree", d))
+    };
+    let (k_lat, k_lon) = (per_degree(d_lat)?, per_degree(d_lon)?);
+    let (rows, cols) = (180 * k_lat + 1, 360 * k_lon + 1);
+    if values.len() != rows * cols {
+        return Err(format!(
+            "expected {} undulations, found {}",
+            rows * cols,
+            values.len()
+        ));
+    }
+
+    let mut bytes = Vec::with_capacity(2 * 181 * 361);
+    for i in (0..rows).step_by(k_lat) {
+        for j in (0..cols).step_by(k_lon) {
+            // Rounded as `Geoid` rounds the grids it parses.
+            let cm = (values[i * cols + j] * 100.0).round();
+            if cm.abs() > f64::from(i16::MAX) {
+                return Err(format!("undulation out of range: {}", values[i * cols + j]));
+            }
+            bytes.extend_from_slice(&(cm as i16).to_le_bytes());
+        }
+    }
+
+    fs::write(output, bytes).map_err(|e| format!("{}: {}", output, e))
+}
@@ -0,0 +1,449 @@
+//! The `calculateDeclination` and `calculateIgrfwmm` calculators, with the query
+//! parameters and response shapes of NOAA's web service.
+
+use geomag::{DateTime, GeodeticLocation, MagneticField, Span, Uncertainty};
+
+use crate::args::{self, Choice, Loaded};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Calculator {
+    /// Declination and its annual change only.
+    Declination,
+    /// Every field element, over a range of dates.
+    Igrfwmm,
+}
+
+impl Calculator {
+    pub fn from_path(path: &str) -> Option<Self> {
+        match path.trim_end_matches('/').rsplit('/').next()? {
+            "calculateDeclination" => Some(Calculator::Declination),
+            "calculateIgrfwmm" => Some(Calculator::Igrfwmm),
+            _ => None,
+        }
+    }
+
+    fn elements(self) -> &'static [Element] {
+        match self {
+            Calculator::Declination => &ELEMENTS[..1],
+            Calculator::Igrfwmm => &ELEMENTS,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ResultFormat {
+    Json,
+    Csv,
+    Xml,
+}
+
+impl ResultFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ResultFormat::Json => "application/json",
+            ResultFormat::Csv => "text/csv",
+            ResultFormat::Xml => "application/xml",
+        }
+    }
+}
+
+/// One field element as named in the responses, with its unit in the XML spelling.
+struct Element {
+    name: &'static str,
+    unit: &'static str,
+    value: fn(&MagneticField) -> f64,
+    sv: fn(&MagneticField) -> f64,
+    uncertainty: fn(&Uncertainty) -> f64,
+}
+
+const ELEMENTS: [Element; 7] = [
+    Element {
+        name: "declination",
+        unit: "Degree",
+        value: |m| m.d.to_degrees(),
+        sv: |m| m.d_dot.to_degrees(),
+        uncertainty: |u| u.d.to_degrees(),
+    },
+    Element {
+        name: "inclination",
+        unit: "Degree",
+        value: |m| m.i.to_degrees(),
+        sv: |m| m.i_dot.to_degrees(),
+        uncertainty: |u| u.i.to_degrees(),
+    },
+    Element {
+        name: "xcomponent",
+        unit: "nT",
+        value: |m| m.x,
+        sv: |m| m.x_dot,
+        uncertainty: |u| u.x,
+    },
+    Element {
+        name: "ycomponent",
+        unit: "nT",
+        value: |m| m.y,
+        sv: |m| m.y_dot,
+        uncertainty: |u| u.y,
+    },
+    Element {
+        name: "zcomponent",
+        unit: "nT",
+        value: |m| m.z,
+        sv: |m| m.z_dot,
+        uncertainty: |u| u.z,
+    },
+    Element {
+        name: "horizontal_intensity",
+        unit: "nT",
+        value: |m| m.h,
+        sv: |m| m.h_dot,
+        uncertainty: |u| u.h,
+    },
+    Element {
+        name: "total_intensity",
+        unit: "nT",
+        value: |m| m.f,
+        sv: |m| m.f_dot,
+        uncertainty: |u| u.f,
+    },
+];
+
+/// Most dates a single request may ask for.
+pub const MAX_ROWS: usize = 10_000;
+
+/// Reference surface of the elevations of a request.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Datum {
+    /// Mean sea level through EGM96, as on the web.
+    Msl,
+    /// The WGS84 ellipsoid, in builds without the `geoid` feature.
+    Ellipsoid,
+}
+
+impl Datum {
+    /// Mean sea level when the build embeds the EGM96 grid.
+    fn available() -> Self {
+        if cfg!(feature = "geoid") {
+            Datum::Msl
+        } else {
+            Datum::Ellipsoid
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Datum::Msl => "mean sea level",
+            Datum::Ellipsoid => "WGS84 ellipsoid",
+        }
+    }
+
+    fn location(self, lon: f64, lat: f64, height: f64) -> Result<GeodeticLocation, String> {
+        args::location(lon, lat, height, self == Datum::Msl)
+    }
+}
+
+/// Decoded query string.
+pub struct Query {
+    params: Vec<(String, String)>,
+}
+
+impl Query {
+    pub fn parse(query: &str) -> Self {
+        let params = query
+            .split('&')
+            .filter(|p| !p.is_empty())
+            .map(|p| match p.split_once('=') {
+                Some((k, v)) => (decode(k), decode(v)),
+                None => (decode(p), String::new()),
+            })
+            .collect();
+
+        Query { params }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.trim())
+            .filter(|v| !v.is_empty())
+    }
+
+    fn number(&self, name: &str) -> Result<Option<f64>, String> {
+        self.get(name)
+            .map(|v| v.parse().map_err(|_| format!("invalid {}: '{}'", name, v)))
+            .transpose()
+    }
+
+    fn whole(&self, name: &str) -> Result<Option<u32>, String> {
+        self.get(name)
+            .map(|v| v.parse().map_err(|_| format!("invalid {}: '{}'", name, v)))
+            .transpose()
+    }
+
+    pub fn format(&self) -> Result<ResultFormat, String> {
+        match self
+            .get("resultFormat")
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            None | Some("json") => Ok(ResultFormat::Json),
+            Some("csv") => Ok(ResultFormat::Csv),
+            Some("xml") => Ok(ResultFormat::Xml),
+            Some(f) => Err(format!("unsupported resultFormat: '{}'", f)),
+        }
+    }
+
+    /// Decimal year of `startYear`, `startMonth` and `startDay` or their `end` versions,
+    /// with the month and day defaulting to the first.
+    fn date(&self, prefix: &str) -> Result<Option<f64>, String> {
+        let Some(year) = self.whole(&format!("{}Year", prefix))? else {
+            return Ok(None);
+        };
+        let month = self.whole(&format!("{}Month", prefix))?.unwrap_or(1);
+        let day = self.whole(&format!("{}Day", prefix))?.unwrap_or(1);
+
+        DateTime::new(year, month, day, 0, 0, 0)
+            .map(|t| Some(t.decimal()))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Signed degrees of `lat1` or `lon1`, negated by a `S` or `W` hemisphere.
+    fn angle(&self, name: &str, negative: &str) -> Result<f64, String> {
+        let value = self.number(name)?.ok_or(format!("missing {}", name))?;
+        let hemisphere = self.get(&format!("{}Hemisphere", name));
+
+        match hemisphere.is_some_and(|h| h.eq_ignore_ascii_case(negative)) {
+            true => Ok(-value.abs()),
+            false => Ok(value),
+        }
+    }
+}
+
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => {
+                let hex = s
+                    .get(i + 1..i + 3)
+                    .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()));
+                match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    Some(b) => {
+                        out.push(b);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            b => out.push(b),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Computes the results of a request and renders them in the requested format.
+pub fn calculate(calculator: Calculator, query: &Query) -> Result<(ResultFormat, String), String> {
+    let format = query.format()?;
+    let lat = query.angle("lat1", "S")?;
+    let lon = query.angle("lon1", "W")?;
+
+    let elevation = query.number("elevation")?.unwrap_or(0.0);
+    let height = match query
+        .get("elevationUnits")
+        .map(str::to_ascii_uppercase)
+        .as_deref()
+    {
+        None | Some("K") => elevation * 1000.0,
+        Some("M") => elevation,
+        Some("F") => elevation * 0.3048,
+        Some(u) => return Err(format!("invalid elevationUnits: '{}'", u)),
+    };
+
+    let choice = match query.get("model") {
+        Some(m) => m.parse::<Choice>()?,
+        None => Choice::Wmm(None),
+    };
+
+    let start = match query.date("start")? {
+        Some(start) => start,
+        None => args::today().map_err(|e| e.to_string())?,
+    };
+    let end = query.date("end")?.unwrap_or(start);
+    let step = query.number("dateStepSize")?.unwrap_or(1.0);
+    let dates = Span::new(start, end, step);
+    dates.validate().map_err(|e| e.to_string())?;
+    if (end - start) / step >= MAX_ROWS as f64 {
+        return Err(format!(
+            "{} to {} by {} gives more than {} dates",
+            start, end, step, MAX_ROWS
+        ));
+    }
+
+    let datum = Datum::available();
+    let location = datum.location(lon, lat, height)?;
+
+    let mut model = Loaded::new(choice, dates.get(dates.len() - 1)).map_err(|e| e.to_string())?;
+    let mut results = Vec::with_capacity(dates.len());
+    for i in 0..dates.len() {
+        let t = dates.get(i);
+        model.retime(t).map_err(|e| e.to_string())?;
+        results.push(Row {
+            date: t,
+            latitude: lat,
+            longitude: lon,
+            elevation: height / 1000.0,
+            field: model.at_location(&location),
+        });
+    }
+
+    let response = Response {
+        calculator,
+        model: model.name(),
+        datum,
+        results,
+    };
+
+    let body = match format {
+        ResultFormat::Json => response.json(),
+        ResultFormat::Csv => response.csv(),
+        ResultFormat::Xml => response.xml(),
+    };
+
+    Ok((format, body))
+}
+
+struct Row {
+    date: f64,
+    latitude: f64,
+    longitude: f64,
+    /// Kilometres.
+    elevation: f64,
+    field: MagneticField,
+}
+
+struct Response {
+    calculator: Calculator,
+    model: &'static str,
+    datum: Datum,
+    results: Vec<Row>,
+}
+
+impl Response {
+    /// Values of a row as named in the responses, in degrees, nT and km.
+    fn values(&self, row: &Row) -> Vec<(String, &'static str, f64)> {
+        let mut values = vec![
+            ("date".to_string(), "", row.date),
+            ("elevation".to_string(), "km", row.elevation),
+            ("latitude".to_string(), "Degree", row.latitude),
+            ("longitude".to_string(), "Degree", row.longitude),
+        ];
+
+        let m = &row.field;
+        for e in self.calculator.elements() {
+            values.push((e.name.to_string(), e.unit, (e.value)(m)));
+            values.push((format!("{}_sv", e.name), e.unit, (e.sv)(m)));
+            if let Some(u) = &m.uncertainty {
+                values.push((
+                    format!("{}_uncertainty", e.name),
+                    e.unit,
+                    (e.uncertainty)(u),
+                ));
+            }
+        }
+
+        values
+    }
+
+    fn json(&self) -> String {
+        let mut s = String::from("{\n  \"result\": [");
+        let mut units = Vec::new();
+
+        for (i, row) in self.results.iter().enumerate() {
+            s += if i == 0 { "\n    {" } else { ",\n    {" };
+            let values = self.values(row);
+            for (j, (name, _, value)) in values.iter().enumerate() {
+                let sep = if j == 0 { "" } else { "," };
+                s += &format!("{}\n      \"{}\": {:.5}", sep, name, value);
+            }
+            s += "\n    }";
+            units = values;
+        }
+
+        s += "\n  ],\n  \"units\": {";
+        let units: Vec<_> = units.iter().filter(|(name, _, _)| name != "date").collect();
+        for (j, (name, unit, _)) in units.iter().enumerate() {
+            let sep = if j == 0 { "" } else { "," };
+            let unit = match *unit {
+                "Degree" => "degrees",
+                "nT" if name.ends_with("_sv") => "nT/yr",
+                unit => unit,
+            };
+            s += &format!("{}\n    \"{}\": \"{}\"", sep, name, unit);
+        }
+
+        format!(
+            "{}\n  }},\n  \"model\": \"{}\",\n  \"elevation_datum\": \"{}\",\n  \"version\": \"{}\"\n}}\n",
+            s,
+            self.model,
+            self.datum.name(),
+            env!("CARGO_PKG_VERSION")
+        )
+    }
+
+    fn xml(&self) -> String {
+        let mut s = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<maggridresult>\n");
+
+        for row in &self.results {
+            s += "  <result>\n";
+            for (name, unit, value) in self.values(row) {
+                match unit {
+                    "" => s += &format!("    <{0}>{1:.5}</{0}>\n", name, value),
+                    unit => {
+                        s += &format!("    <{0} units=\"{2}\">{1:.5}</{0}>\n", name, value, unit)
+                    }
+                }
+            }
+            s += "  </result>\n";
+        }
+
+        format!(
+            "{}  <model>{}</model>\n  <elevationDatum>{}</elevationDatum>\n  <version>{}</version>\n</maggridresult>\n",
+            s,
+            self.model,
+            self.datum.name(),
+            env!("CARGO_PKG_VERSION")
+        )
+    }
+
+    fn csv(&self) -> String {
+        let mut s = format!(
+            "# Model: {}\n# Elevation datum: {}\n",
+            self.model,
+            self.datum.name()
+        );
+
+        if let Some(row) = self.results.first() {
+            let names: Vec<String> = self.values(row).into_iter().map(|v| v.0).collect();
+            s += &format!("# {}\n", names.join(","));
+        }
+
+        for row in &self.results {
+            let values: Vec<String> = self
+                .values(row)
+                .iter()
+                .map(|(_, _, v)| format!("{:.5}", v))
+                .collect();
+            s += &values.join(",");
+            s += "\n";
+        }
+
+        s
+    }
+}
@@ -0,0 +1,214 @@
+//! Offline HTTP service answering the queries of NOAA's geomag web calculators.
+
+// Shared with the command line, which uses more of it.
+#[allow(dead_code)]
+#[path = "../geomag/args.rs"]
+mod args;
+mod calculator;
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::process::ExitCode;
+use std::sync::mpsc::{self, Receiver, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use args::Args;
+use calculator::{Calculator, Query};
+
+/// Threads answering requests.
+const WORKERS: usize = 8;
+/// Connections waiting for a worker before new ones are turned away with a 503.
+const QUEUE: usize = 64;
+/// Longest request line or header line read from a client, in bytes.
+const MAX_LINE: u64 = 8 * 1024;
+/// Longest header section read from a client, in bytes.
+const MAX_HEADERS: u64 = 32 * 1024;
+
+const USAGE: &str = "\
+Usage: geomag-server [--bind <address>]
+
+Serves calculateDeclination and calculateIgrfwmm under any path prefix, such as
+/geomag-web/calculators/calculateDeclination, with the query parameters of NOAA's web
+calculators and resultFormat json, csv or xml. Elevations are above mean sea level in
+builds with the geoid feature, and above the WGS84 ellipsoid otherwise; responses name
+the datum used. A request may ask for at most 10000 dates.
+
+Options:
+    --bind <address>    Address to listen on [default: 127.0.0.1:8080]
+    --help              Print this message
+";
+
+fn main() -> ExitCode {
+    let result = Args::parse(std::env::args().skip(1), &["help"]).and_then(|mut args| {
+        let help = args.flag("help");
+        let bind = args.value("bind").unwrap_or("127.0.0.1:8080".to_string());
+        args.finish()?;
+        Ok((help, bind))
+    });
+
+    let bind = match result {
+        Ok((true, _)) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok((false, bind)) => bind,
+        Err(e) => {
+            eprintln!("geomag-server: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match serve(&bind) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("geomag-server: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn serve(bind: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(bind)?;
+    println!("Listening on http://{}", listener.local_addr()?);
+    io::stdout().flush()?;
+
+    let (sender, receiver) = mpsc::sync_channel(QUEUE);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKERS {
+        let receiver = Arc::clone(&receiver);
+        thread::spawn(move || work(&receiver));
+    }
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("geomag-server: {}", e);
+                continue;
+            }
+        };
+
+        match sender.try_send(stream) {
+            Ok(()) => {}
+            Err(TrySendError::Full(stream)) => {
+                let body = "too many requests, try again later\n";
+                if let Err(e) = refuse(&stream, "503 Service Unavailable", body) {
+                    eprintln!("geomag-server: {}", e);
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => {
+                return Err(io::Error::other("no worker is left to answer requests"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Answers the connections queued by `serve`, one at a time.
+fn work(receiver: &Mutex<Receiver<TcpStream>>) {
+    loop {
+        let stream = match receiver.lock().map(|r| r.recv()) {
+            Ok(Ok(stream)) => stream,
+            _ => return,
+        };
+
+        if let Err(e) = handle(stream) {
+            eprintln!("geomag-server: {}", e);
+        }
+    }
+}
+
+/// Turns a connection away without reading its whole request. What is left of the
+/// request is read off briefly afterwards, as closing with it unread would reset the
+/// connection before the client sees the answer.
+fn refuse(stream: &TcpStream, status: &str, body: &str) -> io::Result<()> {
+    reply(stream, status, "text/plain", body)?;
+    stream.shutdown(Shutdown::Write)?;
+    stream.set_read_timeout(Some(Duration::from_millis(100)))?;
+    // A client that keeps sending past the timeout is not worth reporting.
+    let _ = io::copy(&mut stream.take(64 * 1024), &mut io::sink());
+    Ok(())
+}
+
+/// Answers one request and closes the connection.
+fn handle(stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(10)))?;
+    let mut reader = BufReader::new(&stream);
+
+    let mut request = String::new();
+    if !read_line(&mut reader, &mut request)? {
+        return refuse(&stream, "400 Bad Request", "request line too long\n");
+    }
+
+    // Headers carry nothing the calculators need, but are drained before answering.
+    let mut headers = reader.take(MAX_HEADERS);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if !read_line(&mut headers, &mut line)? {
+            let status = "431 Request Header Fields Too Large";
+            return refuse(&stream, status, "request headers too large\n");
+        }
+
+        if line.trim().is_empty() {
+            break;
+        }
+    }
+
+    let (status, content_type, body) = respond(&request);
+    reply(&stream, status, content_type, &body)
+}
+
+/// Reads one line of at most [`MAX_LINE`] bytes, or nothing at the end of the stream.
+/// Returns false if the line is longer or cut short.
+fn read_line<R: BufRead>(reader: &mut R, line: &mut String) -> io::Result<bool> {
+    let n = reader.by_ref().take(MAX_LINE).read_line(line)?;
+    Ok(n == 0 || line.ends_with('\n'))
+}
+
+fn reply(mut stream: &TcpStream, status: &str, content_type: &str, body: &str) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\n\
+         Access-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn respond(request: &str) -> (&'static str, &'static str, String) {
+    let mut parts = request.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return (
+            "400 Bad Request",
+            "text/plain",
+            "malformed request\n".to_string(),
+        );
+    };
+
+    if method != "GET" {
+        let body = format!("method {} is not allowed\n", method);
+        return ("405 Method Not Allowed", "text/plain", body);
+    }
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let Some(calculator) = Calculator::from_path(path) else {
+        return (
+            "404 Not Found",
+            "text/plain",
+            format!("no calculator at {}\n", path),
+        );
+    };
+
+    match calculator::calculate(calculator, &Query::parse(query)) {
+        Ok((format, body)) => ("200 OK", format.content_type(), body),
+        Err(e) => ("400 Bad Request", "text/plain", format!("{}\n", e)),
+    }
+}
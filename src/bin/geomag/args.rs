@@ -0,0 +1,281 @@
+use std::io;
+use std::str::FromStr;
+
+use geomag::{DateTime, GeodeticLocation, Grid, Span, WmmRelease, IGRF, WMM};
+use geomag::{Error, Geomag, MagneticField};
+
+/// Options given as `--name value`, `--name=value` or, for flags, `--name`.
+pub struct Args {
+    options: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    pub fn parse<I: Iterator<Item = String>>(args: I, flags: &[&str]) -> Result<Self, String> {
+        let mut options = Vec::new();
+        let mut args = args.peekable();
+
+        while let Some(arg) = args.next() {
+            let Some(name) = arg.strip_prefix("--") else {
+                return Err(format!("unexpected argument '{}'", arg));
+            };
+
+            if let Some((name, value)) = name.split_once('=') {
+                options.push((name.to_string(), Some(value.to_string())));
+            } else if flags.contains(&name) {
+                options.push((name.to_string(), None));
+            } else {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for --{}", name))?;
+                options.push((name.to_string(), Some(value)));
+            }
+        }
+
+        Ok(Args { options })
+    }
+
+    pub fn flag(&mut self, name: &str) -> bool {
+        let found = self.options.iter().any(|(n, _)| n == name);
+        self.options.retain(|(n, _)| n != name);
+        found
+    }
+
+    pub fn value(&mut self, name: &str) -> Option<String> {
+        let i = self.options.iter().position(|(n, _)| n == name)?;
+        self.options.remove(i).1
+    }
+
+    pub fn parse_value<T: FromStr>(&mut self, name: &str) -> Result<Option<T>, String> {
+        self.value(name)
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| format!("invalid value for --{}: '{}'", name, v))
+            })
+            .transpose()
+    }
+
+    /// Fails on options that no caller asked for.
+    pub fn finish(self) -> Result<(), String> {
+        match self.options.first() {
+            Some((name, _)) => Err(format!("unknown option --{}", name)),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn parse_latitude(s: &str) -> Option<f64> {
+    parse_angle(s, 'N', 'S')
+}
+
+pub fn parse_longitude(s: &str) -> Option<f64> {
+    parse_angle(s, 'E', 'W')
+}
+
+/// Angle in decimal degrees, optionally followed by a hemisphere letter.
+fn parse_angle(s: &str, positive: char, negative: char) -> Option<f64> {
+    let s = s.trim();
+    let upper = s.to_ascii_uppercase();
+
+    if let Some(v) = upper.strip_suffix(positive) {
+        v.trim().parse().ok()
+    } else if let Some(v) = upper.strip_suffix(negative) {
+        v.trim().parse::<f64>().ok().map(|v| -v)
+    } else {
+        s.parse().ok()
+    }
+}
+
+/// Height in kilometres, or in metres or feet with an `m` or `ft` suffix, returned in
+/// metres.
+pub fn parse_height(s: &str) -> Option<f64> {
+    let s = s.trim().to_ascii_lowercase();
+
+    if let Some(v) = s.strip_suffix("km") {
+        v.trim().parse::<f64>().ok().map(|v| v * 1000.0)
+    } else if let Some(v) = s.strip_suffix("ft") {
+        v.trim().parse::<f64>().ok().map(|v| v * 0.3048)
+    } else if let Some(v) = s.strip_suffix('m') {
+        v.trim().parse().ok()
+    } else {
+        s.parse::<f64>().ok().map(|v| v * 1000.0)
+    }
+}
+
+/// `start:end:step`, or a single value, with each part read by `parse`.
+pub fn parse_span(s: &str, parse: fn(&str) -> Option<f64>) -> Option<Span> {
+    let mut parts = s.split(':');
+    let start = parse(parts.next()?)?;
+
+    match (parts.next(), parts.next(), parts.next()) {
+        (None, _, _) => Some(Span::single(start)),
+        (Some(end), Some(step), None) => Some(Span::new(start, parse(end)?, parse(step)?)),
+        _ => None,
+    }
+}
+
+/// Decimal year, or a calendar date as `YYYY-MM-DD` with an optional `THH:MM[:SS]` time.
+pub fn parse_date(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if !s.contains('-') || s.starts_with('-') {
+        return s.parse().ok();
+    }
+
+    let (date, time) = match s.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (s, None),
+    };
+
+    let mut date = date.split('-').map(u32::from_str);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    if date.next().is_some() {
+        return None;
+    }
+
+    let (mut hour, mut min, mut sec) = (0, 0, 0);
+    if let Some(time) = time {
+        let mut time = time.trim_end_matches('Z').split(':').map(u32::from_str);
+        hour = time.next()?.ok()?;
+        min = time.next()?.ok()?;
+        sec = match time.next() {
+            Some(v) => v.ok()?,
+            None => 0,
+        };
+    }
+
+    DateTime::new(year, month, day, hour, min, sec)
+        .ok()
+        .map(|t| t.decimal())
+}
+
+/// Location from degrees and a height in metres, converted from mean sea level if asked.
+pub fn location(lon: f64, lat: f64, height: f64, msl: bool) -> Result<GeodeticLocation, String> {
+    if !msl {
+        return GeodeticLocation::new(lon, lat, height).map_err(|e| e.to_string());
+    }
+
+    #[cfg(feature = "geoid")]
+    return GeodeticLocation::from_msl(lon, lat, height).map_err(|e| e.to_string());
+
+    #[cfg(not(feature = "geoid"))]
+    Err("heights above mean sea level need the geoid feature".to_string())
+}
+
+/// Today's date from the system clock, as a decimal year.
+pub fn today() -> io::Result<f64> {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| io::Error::other(format!("cannot read today's date: {}", e)))?
+        .as_secs();
+
+    // Civil date from days since 1970-01-01, after Howard Hinnant's algorithm.
+    let z = secs / 86400 + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    DateTime::new(year as u32, month as u32, day as u32, 0, 0, 0)
+        .map(|t| t.decimal())
+        .map_err(|e| io::Error::other(format!("cannot read today's date: {}", e)))
+}
+
+/// Model selected on the command line.
+#[derive(Copy, Clone)]
+pub enum Choice {
+    /// The latest WMM release valid at each date, or a fixed one.
+    Wmm(Option<WmmRelease>),
+    Igrf,
+}
+
+impl FromStr for Choice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wmm" => Ok(Choice::Wmm(None)),
+            "igrf" | "igrf14" => Ok(Choice::Igrf),
+            _ => s
+                .parse()
+                .map(|r| Choice::Wmm(Some(r)))
+                .map_err(|_| format!("unknown model '{}'", s)),
+        }
+    }
+}
+
+/// A model built for one selection, moved between dates as they are requested.
+pub enum Loaded {
+    Wmm {
+        model: WMM,
+        release: WmmRelease,
+        fixed: bool,
+    },
+    Igrf(IGRF),
+}
+
+impl Loaded {
+    pub fn new(choice: Choice, decimal: f64) -> Result<Self, Error> {
+        let (release, fixed) = match choice {
+            Choice::Igrf => return Ok(Loaded::Igrf(IGRF::new(decimal)?)),
+            Choice::Wmm(Some(release)) => (release, true),
+            Choice::Wmm(None) => match WmmRelease::containing(decimal) {
+                Some(release) => (release, false),
+                // Let the model report the supported range.
+                None => (WMM::new(decimal).map(|_| WmmRelease::ALL[0])?, false),
+            },
+        };
+
+        Ok(Loaded::Wmm {
+            model: WMM::release(release, decimal)?,
+            release,
+            fixed,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Loaded::Wmm { release, .. } => release.name(),
+            Loaded::Igrf(_) => "IGRF14",
+        }
+    }
+
+    /// Moves to `decimal`, switching to a later WMM release unless one was chosen.
+    pub fn retime(&mut self, decimal: f64) -> Result<(), Error> {
+        match self {
+            Loaded::Wmm {
+                model,
+                release,
+                fixed,
+            } => {
+                if !*fixed {
+                    // Outside every release, WMM::new reports the range they cover.
+                    *release = match WmmRelease::containing(decimal) {
+                        Some(r) => r,
+                        None => return WMM::new(decimal).map(|_| ()),
+                    };
+                }
+                *model = WMM::release(*release, decimal)?;
+            }
+            Loaded::Igrf(igrf) => *igrf = igrf.at_time(decimal)?,
+        }
+
+        Ok(())
+    }
+
+    pub fn at_location(&self, l: &GeodeticLocation) -> MagneticField {
+        match self {
+            Loaded::Wmm { model, .. } => model.at_location(l),
+            Loaded::Igrf(igrf) => igrf.at_location(l),
+        }
+    }
+
+    pub fn at_grid_map<U, F: FnMut(MagneticField) -> U>(&self, grid: &Grid, f: F) -> Vec<U> {
+        match self {
+            Loaded::Wmm { model, .. } => model.at_grid_map(grid, f),
+            Loaded::Igrf(igrf) => igrf.at_grid_map(grid, f),
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Input records of the `file` command.
+
+use geomag::{DateTime, EcefLocation, GeocentricLocation, GeodeticLocation};
+
+use crate::args;
+
+/// Date and position of one input line.
+pub struct Record {
+    pub decimal: f64,
+    pub location: GeodeticLocation,
+}
+
+/// Reads a line of the `wmm_file` and `geomag70` input layout:
+///
+/// ```text
+/// date coord-system altitude latitude longitude
+/// 2025.5 D K0.5 24.5 102
+/// 2025,6,1 C K6400 -45,30,0 170
+/// ```
+///
+/// Dates are decimal years or `yyyy,mm,dd`. The coordinate system is `D` for geodetic, or
+/// `C` for geocentric where the altitude is the radius from the centre. Altitudes are
+/// prefixed with `K`, `M` or `F` for kilometres, metres or feet above mean sea level, or
+/// `E` for kilometres above the WGS84 ellipsoid. Angles are decimal degrees or `d,m,s`.
+pub fn parse_legacy(line: &str) -> Result<Record, String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let [date, system, altitude, lat, lon] = fields[..] else {
+        return Err(format!("expected 5 fields, found {}", fields.len()));
+    };
+
+    let decimal = parse_legacy_date(date).ok_or(format!("invalid date '{}'", date))?;
+    let lat = parse_dms(lat).ok_or(format!("invalid latitude '{}'", lat))?;
+    let lon = parse_dms(lon).ok_or(format!("invalid longitude '{}'", lon))?;
+
+    let (code, value) = altitude.split_at(altitude.len().min(1));
+    let value: f64 = value
+        .parse()
+        .map_err(|_| format!("invalid altitude '{}'", altitude))?;
+    let (height, msl) = match code.to_ascii_uppercase().as_str() {
+        "K" => (value * 1000.0, true),
+        "M" => (value, true),
+        "F" => (value * 0.3048, true),
+        "E" => (value * 1000.0, false),
+        _ => return Err(format!("invalid altitude '{}'", altitude)),
+    };
+
+    let location = match system.to_ascii_uppercase().as_str() {
+        "D" => args::location(lon, lat, height, msl)?,
+        "C" => {
+            let l = GeocentricLocation::new(lon, lat, height).map_err(|e| e.to_string())?;
+            GeodeticLocation::from(&EcefLocation::from(&l))
+        }
+        _ => return Err(format!("invalid coordinate system '{}'", system)),
+    };
+
+    Ok(Record { decimal, location })
+}
+
+fn parse_legacy_date(s: &str) -> Option<f64> {
+    if !s.contains(',') {
+        return s.parse().ok();
+    }
+
+    let mut parts = s.split(',').map(str::parse::<u32>);
+    let (year, month, day) = (
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+    );
+    if parts.next().is_some() {
+        return None;
+    }
+
+    DateTime::new(year, month, day, 0, 0, 0)
+        .ok()
+        .map(|t| t.decimal())
+}
+
+/// Decimal degrees, or `d,m[,s]` with the sign of the degrees.
+fn parse_dms(s: &str) -> Option<f64> {
+    let mut parts = s.split(',');
+    let deg = parts.next()?;
+    let min: f64 = parts.next().map_or(Some(0.0), |v| v.parse().ok())?;
+    let sec: f64 = parts.next().map_or(Some(0.0), |v| v.parse().ok())?;
+    if parts.next().is_some() {
+        return None;
+    }
+
+    let value = deg.parse::<f64>().ok()?.abs() + min / 60.0 + sec / 3600.0;
+    Some(if deg.starts_with('-') { -value } else { value })
+}
+
+/// Positions of the input columns of a CSV file, found by name in its header.
+pub struct CsvColumns {
+    date: usize,
+    latitude: usize,
+    longitude: usize,
+    height: Option<usize>,
+    msl: bool,
+}
+
+impl CsvColumns {
+    /// `names` are the date, latitude, longitude and height columns. The height column
+    /// may be missing, for points on the ellipsoid or at sea level.
+    pub fn from_header(header: &str, names: [&str; 4], msl: bool) -> Result<Self, String> {
+        let columns = split_csv(header)?;
+        let find = |name: &str| {
+            columns
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(name))
+                .ok_or(format!("no column '{}' in header", name))
+        };
+
+        Ok(CsvColumns {
+            date: find(names[0])?,
+            latitude: find(names[1])?,
+            longitude: find(names[2])?,
+            height: find(names[3]).ok(),
+            msl,
+        })
+    }
+
+    /// Reads one row, with the values parsed as for the `point` command.
+    pub fn parse(&self, line: &str) -> Result<Record, String> {
+        let values = split_csv(line)?;
+        let get = |i: usize| {
+            values
+                .get(i)
+                .map(String::as_str)
+                .ok_or(format!("expected at least {} columns", i + 1))
+        };
+
+        let date = get(self.date)?;
+        let decimal = args::parse_date(date).ok_or(format!("invalid date '{}'", date))?;
+        let lat = get(self.latitude)?;
+        let lat = args::parse_latitude(lat).ok_or(format!("invalid latitude '{}'", lat))?;
+        let lon = get(self.longitude)?;
+        let lon = args::parse_longitude(lon).ok_or(format!("invalid longitude '{}'", lon))?;
+        let height = match self.height {
+            Some(i) => {
+                let h = get(i)?;
+                args::parse_height(h).ok_or(format!("invalid height '{}'", h))?
+            }
+            None => 0.0,
+        };
+
+        let location = args::location(lon, lat, height, self.msl)?;
+        Ok(Record { decimal, location })
+    }
+}
+
+/// Splits a CSV line into trimmed fields. Fields may be quoted to contain commas, with
+/// `""` standing for a quote inside them.
+fn split_csv(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+
+    loop {
+        let mut field = String::new();
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next() {
+                    Some('"') if chars.next_if_eq(&'"').is_some() => field.push('"'),
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err("unterminated quoted field".to_string()),
+                }
+            }
+
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            if !matches!(chars.peek(), Some(',') | None) {
+                return Err(format!("unexpected text after quoted field \"{}\"", field));
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| *c != ',') {
+                field.push(c);
+            }
+            field.truncate(field.trim_end().len());
+        }
+
+        fields.push(field);
+        if chars.next().is_none() {
+            return Ok(fields);
+        }
+    }
+}
+
+/// Quotes a CSV field if it contains a comma, a quote or a line break.
+pub fn quote_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
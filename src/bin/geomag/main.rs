@@ -0,0 +1,379 @@
+//! Command line calculator for the embedded geomagnetic field models.
+
+mod args;
+mod file;
+mod output;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+use geomag::{Error, Grid, MagneticField, Span};
+
+use args::{Args, Choice, Loaded};
+use file::{CsvColumns, Record};
+use output::{Column, Format, Point, COMPONENTS};
+
+const USAGE: &str = "\
+Usage: geomag [point] --lat <deg> --lon <deg> [options]
+       geomag grid --lat <span> --lon <span> [options]
+       geomag file [--input <path>] [--output <path>] [options]
+
+A span is `start:end:step` or a single value, in the units of the option. Legacy file
+altitudes coded K, M or F are above mean sea level, which needs the geoid feature.
+
+Options:
+    --lat <deg>         Latitude in decimal degrees, or with an N/S suffix
+    --lon <deg>         Longitude in decimal degrees, or with an E/W suffix
+    --height <h>        Height in km, or in m or ft with a suffix [default: 0]
+    --msl               Height is above mean sea level rather than the ellipsoid,
+                        which needs the geoid feature
+    --date <date>       YYYY-MM-DD[THH:MM[:SS]] or a decimal year [default: today]
+    --model <model>     wmm, wmm2020, wmm2025 or igrf [default: wmm]
+    --format <format>   human, json or csv [default: human]
+    --components <list> Columns of grid, such as x,d,d_dot [default: d]
+    --input <path>      Records to read, in the wmm_file layout or CSV [default: stdin]
+    --output <path>     Where to write the records with the field [default: stdout]
+    --input-format <f>  legacy or csv [default: csv for .csv files, else legacy]
+    --date-column <c>   CSV column names of the date, latitude, longitude and
+    --lat-column <c>    height, with the values read as for point
+    --lon-column <c>    [default: date, latitude, longitude, height]
+    --height-column <c>
+    --help              Print this message
+";
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1).peekable();
+    let command = match args.peek().map(String::as_str) {
+        Some(c) if !c.starts_with("--") => args.next(),
+        _ => None,
+    };
+
+    let result = match command.as_deref() {
+        None | Some("point") => Args::parse(args, &["msl", "help"])
+            .map_err(Failure::from)
+            .and_then(point),
+        Some("grid") => Args::parse(args, &["help"])
+            .map_err(Failure::from)
+            .and_then(grid),
+        Some("file") => Args::parse(args, &["msl", "help"])
+            .map_err(Failure::from)
+            .and_then(file),
+        Some(c) => Err(Failure::Usage(format!("unknown command '{}'", c))),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Failure::Usage(e)) => {
+            eprintln!("geomag: {}\n\n{}", e, USAGE);
+            ExitCode::from(2)
+        }
+        Err(Failure::Field(e)) => {
+            eprintln!("geomag: {}", e);
+            ExitCode::FAILURE
+        }
+        Err(Failure::Io(e)) => {
+            eprintln!("geomag: {}", e);
+            ExitCode::FAILURE
+        }
+        Err(Failure::Records { failed, total }) => {
+            eprintln!("geomag: {} of {} records failed", failed, total);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Why a command failed, which decides whether the usage is worth printing.
+enum Failure {
+    Usage(String),
+    Field(geomag::Error),
+    Io(io::Error),
+    /// Records of a file that could not be evaluated, each already reported.
+    Records {
+        failed: usize,
+        total: usize,
+    },
+}
+
+impl From<String> for Failure {
+    fn from(e: String) -> Self {
+        Failure::Usage(e)
+    }
+}
+
+impl From<geomag::Error> for Failure {
+    fn from(e: geomag::Error) -> Self {
+        Failure::Field(e)
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Failure::Io(e)
+    }
+}
+
+fn parsed<T>(
+    args: &mut Args,
+    name: &str,
+    parse: fn(&str) -> Option<T>,
+) -> Result<Option<T>, String> {
+    args.value(name)
+        .map(|v| parse(&v).ok_or(format!("invalid value for --{}: '{}'", name, v)))
+        .transpose()
+}
+
+fn required<T>(value: Option<T>, name: &str) -> Result<T, String> {
+    value.ok_or_else(|| format!("missing --{}", name))
+}
+
+fn point(mut args: Args) -> Result<(), Failure> {
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    let lat = required(parsed(&mut args, "lat", args::parse_latitude)?, "lat")?;
+    let lon = required(parsed(&mut args, "lon", args::parse_longitude)?, "lon")?;
+    let height = parsed(&mut args, "height", args::parse_height)?.unwrap_or(0.0);
+    let msl = args.flag("msl");
+    let decimal = match parsed(&mut args, "date", args::parse_date)? {
+        Some(decimal) => decimal,
+        None => args::today()?,
+    };
+    let choice = args
+        .parse_value::<Choice>("model")?
+        .unwrap_or(Choice::Wmm(None));
+    let format = args
+        .parse_value::<Format>("format")?
+        .unwrap_or(Format::Human);
+    args.finish()?;
+
+    let l = args::location(lon, lat, height, msl).map_err(Failure::Usage)?;
+    let model = Loaded::new(choice, decimal)?;
+    let field = model.at_location(&l);
+
+    let p = Point {
+        model: model.name(),
+        decimal,
+        location: l,
+        height,
+        msl,
+        field,
+    };
+
+    let mut out = io::stdout().lock();
+    output::write_point(&mut out, format, &p)?;
+    Ok(out.flush()?)
+}
+
+/// Streams the selected components over every point of the grid at every date, as CSV.
+fn grid(mut args: Args) -> Result<(), Failure> {
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    let span = |args: &mut Args, name: &str, parse: fn(&str) -> Option<f64>| {
+        args.value(name)
+            .map(|v| {
+                args::parse_span(&v, parse).ok_or(format!("invalid value for --{}: '{}'", name, v))
+            })
+            .transpose()
+    };
+
+    let lat = required(span(&mut args, "lat", args::parse_latitude)?, "lat")?;
+    let lon = required(span(&mut args, "lon", args::parse_longitude)?, "lon")?;
+    let height = span(&mut args, "height", args::parse_height)?.unwrap_or(Span::single(0.0));
+    let dates = match span(&mut args, "date", args::parse_date)? {
+        Some(dates) => dates,
+        None => Span::single(args::today()?),
+    };
+    let choice = args
+        .parse_value::<Choice>("model")?
+        .unwrap_or(Choice::Wmm(None));
+    let columns = parsed(&mut args, "components", Column::parse_list)?;
+    let columns = columns.unwrap_or_else(|| Column::parse_list("d").unwrap_or_default());
+    args.finish()?;
+
+    let grid = Grid::new(lat, lon, height)?;
+    dates.validate()?;
+
+    // Fails on the last date before anything is written.
+    let last = dates.get(dates.len() - 1);
+    let mut model = Loaded::new(choice, last)?;
+    model.retime(dates.start)?;
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    write!(out, "date,latitude,longitude,height")?;
+    for c in &columns {
+        write!(out, ",{}", c.name())?;
+    }
+    writeln!(out)?;
+
+    let (n_lon, n_lat) = (grid.longitude.len(), grid.latitude.len());
+    for k in 0..dates.len() {
+        let t = dates.get(k);
+        model.retime(t)?;
+
+        // Rows are written as the points are evaluated, so no field outlives its row.
+        let mut i = 0;
+        let mut written = Ok(());
+        model.at_grid_map(&grid, |m| {
+            let lon = grid.longitude.get(i % n_lon);
+            let lat = grid.latitude.get(i / n_lon % n_lat);
+            let h = grid.height.get(i / n_lon / n_lat);
+            i += 1;
+
+            if written.is_ok() {
+                written = write!(out, "{},{},{},{}", t, lat, lon, h)
+                    .and_then(|_| {
+                        columns
+                            .iter()
+                            .try_for_each(|c| write!(out, ",{}", c.get(&m)))
+                    })
+                    .and_then(|_| writeln!(out));
+            }
+        });
+        written?;
+    }
+
+    Ok(out.flush()?)
+}
+
+/// Appends the field to every record of a file, reporting bad records without stopping.
+fn file(mut args: Args) -> Result<(), Failure> {
+    if args.flag("help") {
+        print!("{}", USAGE);
+        return Ok(());
+    }
+
+    let input = args.value("input").filter(|p| p != "-");
+    let output = args.value("output").filter(|p| p != "-");
+    let csv = match args.value("input-format").as_deref() {
+        Some("csv") => true,
+        Some("legacy") => false,
+        Some(f) => return Err(Failure::Usage(format!("unknown input format '{}'", f))),
+        None => input
+            .as_ref()
+            .is_some_and(|p| p.to_ascii_lowercase().ends_with(".csv")),
+    };
+    let names = [
+        args.value("date-column").unwrap_or("date".to_string()),
+        args.value("lat-column").unwrap_or("latitude".to_string()),
+        args.value("lon-column").unwrap_or("longitude".to_string()),
+        args.value("height-column").unwrap_or("height".to_string()),
+    ];
+    let msl = args.flag("msl");
+    let choice = args
+        .parse_value::<Choice>("model")?
+        .unwrap_or(Choice::Wmm(None));
+    args.finish()?;
+
+    let reader: Box<dyn BufRead> = match input {
+        Some(p) => Box::new(BufReader::new(File::open(p)?)),
+        None => Box::new(io::stdin().lock()),
+    };
+    let mut out: Box<dyn Write> = match output {
+        Some(p) => Box::new(BufWriter::new(File::create(p)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+
+    let mut lines = reader.lines();
+    let columns = if csv {
+        let header = lines.next().transpose()?.unwrap_or_default();
+        let names = names.each_ref().map(String::as_str);
+        let columns = CsvColumns::from_header(&header, names, msl).map_err(Failure::Usage)?;
+
+        write!(out, "{}", header)?;
+        for c in &COMPONENTS {
+            write!(out, ",{0},{0}_dot", c.key)?;
+        }
+        writeln!(out, ",error")?;
+        Some(columns)
+    } else {
+        write!(out, "# date coord-system altitude latitude longitude")?;
+        for c in &COMPONENTS {
+            write!(out, " {0} {0}_dot", c.key)?;
+        }
+        writeln!(out)?;
+        None
+    };
+
+    let mut model = None;
+    let (mut total, mut failed) = (0, 0);
+    let first = if csv { 2 } else { 1 };
+
+    for (number, line) in (first..).zip(lines) {
+        let line = line?;
+        if line.trim().is_empty() || (!csv && line.trim_start().starts_with('#')) {
+            writeln!(out, "{}", line)?;
+            continue;
+        }
+
+        total += 1;
+        let record = match &columns {
+            Some(columns) => columns.parse(&line),
+            None => file::parse_legacy(&line),
+        };
+        let field =
+            record.and_then(|r| evaluate(&mut model, choice, &r).map_err(|e| e.to_string()));
+
+        match (field, csv) {
+            (Ok(m), true) => {
+                write!(out, "{}", line)?;
+                for c in &COMPONENTS {
+                    write!(out, ",{},{}", c.value(&m), c.rate(&m))?;
+                }
+                writeln!(out, ",")?;
+            }
+            (Ok(m), false) => {
+                write!(out, "{}", line)?;
+                for c in &COMPONENTS {
+                    let precision = if c.is_angle() { 4 } else { 1 };
+                    write!(
+                        out,
+                        " {:.*} {:.*}",
+                        precision,
+                        c.value(&m),
+                        precision,
+                        c.rate(&m)
+                    )?;
+                }
+                writeln!(out)?;
+            }
+            (Err(e), csv) => {
+                failed += 1;
+                eprintln!("geomag: line {}: {}", number, e);
+                if csv {
+                    let empty = ",".repeat(2 * COMPONENTS.len());
+                    writeln!(out, "{}{},{}", line, empty, file::quote_csv(&e))?;
+                } else {
+                    writeln!(out, "{} ERROR: {}", line, e)?;
+                }
+            }
+        }
+    }
+
+    out.flush()?;
+    match failed {
+        0 => Ok(()),
+        _ => Err(Failure::Records { failed, total }),
+    }
+}
+
+/// Evaluates one record, keeping the model between records to move it in time.
+fn evaluate(
+    model: &mut Option<Loaded>,
+    choice: Choice,
+    r: &Record,
+) -> Result<MagneticField, Error> {
+    let mut m = match model.take() {
+        Some(m) => m,
+        None => Loaded::new(choice, r.decimal)?,
+    };
+
+    let field = m.retime(r.decimal).map(|_| m.at_location(&r.location));
+    *model = Some(m);
+    field
+}
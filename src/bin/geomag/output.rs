@@ -0,0 +1,304 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use geomag::navigation::grid_variation;
+use geomag::{GeodeticLocation, MagneticField, Zone};
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Format {
+    Human,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "human" | "text" => Ok(Format::Human),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format '{}'", s)),
+        }
+    }
+}
+
+/// One field element, with the angles given in degrees.
+pub struct Component {
+    pub key: &'static str,
+    pub label: &'static str,
+    pub unit: &'static str,
+    value: fn(&MagneticField) -> f64,
+    rate: fn(&MagneticField) -> f64,
+}
+
+impl Component {
+    pub fn value(&self, m: &MagneticField) -> f64 {
+        (self.value)(m)
+    }
+
+    pub fn rate(&self, m: &MagneticField) -> f64 {
+        (self.rate)(m)
+    }
+
+    pub fn is_angle(&self) -> bool {
+        self.unit == "deg"
+    }
+}
+
+pub const COMPONENTS: [Component; 7] = [
+    Component {
+        key: "x",
+        label: "North intensity",
+        unit: "nT",
+        value: |m| m.x,
+        rate: |m| m.x_dot,
+    },
+    Component {
+        key: "y",
+        label: "East intensity",
+        unit: "nT",
+        value: |m| m.y,
+        rate: |m| m.y_dot,
+    },
+    Component {
+        key: "z",
+        label: "Vertical intensity",
+        unit: "nT",
+        value: |m| m.z,
+        rate: |m| m.z_dot,
+    },
+    Component {
+        key: "h",
+        label: "Horizontal intensity",
+        unit: "nT",
+        value: |m| m.h,
+        rate: |m| m.h_dot,
+    },
+    Component {
+        key: "f",
+        label: "Total intensity",
+        unit: "nT",
+        value: |m| m.f,
+        rate: |m| m.f_dot,
+    },
+    Component {
+        key: "i",
+        label: "Inclination",
+        unit: "deg",
+        value: |m| m.i.to_degrees(),
+        rate: |m| m.i_dot.to_degrees(),
+    },
+    Component {
+        key: "d",
+        label: "Declination",
+        unit: "deg",
+        value: |m| m.d.to_degrees(),
+        rate: |m| m.d_dot.to_degrees(),
+    },
+];
+
+/// A component or its annual change, selected as an output column.
+#[derive(Copy, Clone)]
+pub struct Column {
+    component: &'static Component,
+    rate: bool,
+}
+
+impl Column {
+    /// Parses a component key such as `d`, or `d_dot` for its annual change.
+    pub fn parse(key: &str) -> Option<Self> {
+        let key = key.trim().to_ascii_lowercase();
+        let (key, rate) = match key.strip_suffix("_dot") {
+            Some(key) => (key, true),
+            None => (key.as_str(), false),
+        };
+
+        let component = COMPONENTS.iter().find(|c| c.key == key)?;
+        Some(Column { component, rate })
+    }
+
+    /// Comma separated column keys.
+    pub fn parse_list(s: &str) -> Option<Vec<Self>> {
+        s.split(',').map(Column::parse).collect()
+    }
+
+    pub fn name(&self) -> String {
+        let suffix = if self.rate { "_dot" } else { "" };
+        format!("{}{}", self.component.key, suffix)
+    }
+
+    pub fn get(&self, m: &MagneticField) -> f64 {
+        if self.rate {
+            self.component.rate(m)
+        } else {
+            self.component.value(m)
+        }
+    }
+}
+
+/// A field result together with where, when and from which model it was computed.
+pub struct Point<'a> {
+    pub model: &'a str,
+    pub decimal: f64,
+    pub location: GeodeticLocation,
+    /// Height as given, in metres, and whether it was above mean sea level.
+    pub height: f64,
+    pub msl: bool,
+    pub field: MagneticField,
+}
+
+fn zone_name(zone: Zone) -> &'static str {
+    match zone {
+        Zone::Normal => "normal",
+        Zone::Caution => "caution",
+        Zone::Blackout => "blackout",
+    }
+}
+
+pub fn write_point<W: Write>(out: &mut W, format: Format, p: &Point) -> io::Result<()> {
+    match format {
+        Format::Human => write_human(out, p),
+        Format::Json => writeln!(out, "{}", json(p)),
+        Format::Csv => {
+            writeln!(out, "{}", CSV_HEADER)?;
+            writeln!(out, "{}", csv_row(p))
+        }
+    }
+}
+
+fn write_human<W: Write>(out: &mut W, p: &Point) -> io::Result<()> {
+    let lat = p.location.latitude.to_degrees();
+    let lon = p.location.longitude.to_degrees();
+    let datum = if p.msl { "mean sea level" } else { "ellipsoid" };
+
+    writeln!(out, "Model:     {}", p.model)?;
+    writeln!(out, "Date:      {:.3}", p.decimal)?;
+    writeln!(
+        out,
+        "Location:  {:.4}° {}, {:.4}° {}, {:.3} km above {}",
+        lat.abs(),
+        if lat < 0.0 { 'S' } else { 'N' },
+        lon.abs(),
+        if lon < 0.0 { 'W' } else { 'E' },
+        p.height / 1000.0,
+        datum
+    )?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:<26}{:>14}{:>16}{:>14}",
+        "", "Value", "Annual change", "Uncertainty"
+    )?;
+
+    let m = &p.field;
+    for c in &COMPONENTS {
+        let name = format!("{} ({})", c.label, c.key.to_ascii_uppercase());
+        let sigma = m.uncertainty.map(|u| match c.key {
+            "x" => u.x,
+            "y" => u.y,
+            "z" => u.z,
+            "h" => u.h,
+            "f" => u.f,
+            "i" => u.i.to_degrees(),
+            _ => u.d.to_degrees(),
+        });
+
+        let (value, rate, sigma) = if c.is_angle() {
+            (
+                format!("{:.2}°", c.value(m)),
+                format!("{:.1}'/yr", c.rate(m) * 60.0),
+                sigma.map(|s| format!("±{:.2}°", s)),
+            )
+        } else {
+            (
+                format!("{:.1} nT", c.value(m)),
+                format!("{:.1} nT/yr", c.rate(m)),
+                sigma.map(|s| format!("±{:.0} nT", s)),
+            )
+        };
+
+        writeln!(
+            out,
+            "{:<26}{:>14}{:>16}{:>14}",
+            name,
+            value,
+            rate,
+            sigma.unwrap_or_default()
+        )?;
+    }
+
+    let gv = grid_variation(m, &p.location);
+    writeln!(
+        out,
+        "{:<26}{:>14}{:>16}",
+        "Grid variation (GV)",
+        format!("{:.2}°", gv.value.to_degrees()),
+        format!("{:.1}'/yr", gv.rate.to_degrees() * 60.0)
+    )?;
+
+    match m.zone {
+        Zone::Normal => Ok(()),
+        Zone::Caution => writeln!(out, "\nCaution: compass accuracy may be degraded"),
+        Zone::Blackout => writeln!(out, "\nWarning: compass is unreliable in the blackout zone"),
+    }
+}
+
+fn json(p: &Point) -> String {
+    let m = &p.field;
+    let mut s = format!(
+        "{{\"model\":\"{}\",\"date\":{},\"latitude\":{},\"longitude\":{},\"height\":{},\"datum\":\"{}\"",
+        p.model,
+        p.decimal,
+        p.location.latitude.to_degrees(),
+        p.location.longitude.to_degrees(),
+        p.height,
+        if p.msl { "msl" } else { "ellipsoid" }
+    );
+
+    for c in &COMPONENTS {
+        s += &format!(",\"{0}\":{1},\"{0}_dot\":{2}", c.key, c.value(m), c.rate(m));
+    }
+
+    s += &format!(",\"zone\":\"{}\"", zone_name(m.zone));
+    match m.uncertainty {
+        Some(u) => {
+            s += &format!(
+            ",\"uncertainty\":{{\"x\":{},\"y\":{},\"z\":{},\"h\":{},\"f\":{},\"i\":{},\"d\":{}}}",
+            u.x,
+            u.y,
+            u.z,
+            u.h,
+            u.f,
+            u.i.to_degrees(),
+            u.d.to_degrees()
+        )
+        }
+        None => s += ",\"uncertainty\":null",
+    }
+    let gv = grid_variation(m, &p.location);
+    s += &format!(",\"gv\":{}", gv.value.to_degrees());
+
+    s + "}"
+}
+
+pub const CSV_HEADER: &str =
+    "model,date,latitude,longitude,height,x,x_dot,y,y_dot,z,z_dot,h,h_dot,f,f_dot,i,i_dot,d,d_dot";
+
+pub fn csv_row(p: &Point) -> String {
+    let mut s = format!(
+        "{},{},{},{},{}",
+        p.model,
+        p.decimal,
+        p.location.latitude.to_degrees(),
+        p.location.longitude.to_degrees(),
+        p.height
+    );
+
+    for c in &COMPONENTS {
+        s += &format!(",{},{}", c.value(&p.field), c.rate(&p.field));
+    }
+
+    s
+}
@@ -1,9 +1,15 @@
+use crate::error::{DateTimeField, Error};
+#[cfg(feature = "libm")]
+use crate::num::Float;
 use crate::num::NumFrom;
 
 macro_rules! is_valid {
-    ($value:expr, $min:expr, $max:expr) => {
+    ($value:expr, $min:expr, $max:expr, $field:expr) => {
         if $value < $min || $value > $max {
-            return None;
+            return Err(Error::InvalidDateTime {
+                field: $field,
+                value: $value,
+            });
         }
     };
 }
@@ -20,15 +26,23 @@ pub struct DateTime {
 
 impl DateTime {
     #[allow(unused_comparisons)]
-    pub fn new(year: u32, month: u32, day: u32, hour: u32, min: u32, sec: u32) -> Option<Self> {
-        is_valid!(year, 1_000, 10_000);
-        is_valid!(month, 1, 12);
-        is_valid!(day, 1, 31);
-        is_valid!(hour, 0, 24);
-        is_valid!(min, 0, 60);
-        is_valid!(sec, 0, 60);
-
-        Some(DateTime {
+    pub fn new(
+        year: u32,
+        month: u32,
+        day: u32,
+        hour: u32,
+        min: u32,
+        sec: u32,
+    ) -> Result<Self, Error> {
+        is_valid!(year, 1_000, 10_000, DateTimeField::Year);
+        is_valid!(month, 1, 12, DateTimeField::Month);
+        let days = unsafe { days_of_month(month, is_leap_year(year)) };
+        is_valid!(day, 1, days, DateTimeField::Day);
+        is_valid!(hour, 0, 24, DateTimeField::Hour);
+        is_valid!(min, 0, 60, DateTimeField::Minute);
+        is_valid!(sec, 0, 60, DateTimeField::Second);
+
+        Ok(DateTime {
             year,
             month,
             day,
@@ -70,6 +84,34 @@ impl DateTime {
             f64::from_unchecked(self.year) + d
         }
     }
+
+    /// Julian date, taking the time as UT.
+    pub fn julian_date(&self) -> f64 {
+        let a = (14 - self.month) / 12;
+        let y = self.year + 4800 - a;
+        let m = self.month + 12 * a - 3;
+        let jdn = self.day + (153 * m + 2) / 5 + 365 * y + y / 4 - y / 100 + y / 400 - 32045;
+
+        unsafe {
+            let s = f64::from_unchecked(self.hour * 3600 + self.min * 60 + self.sec);
+            f64::from_unchecked(jdn) - 0.5 + s / 86400.0
+        }
+    }
+
+    /// Greenwich mean sidereal time in radians, in `0..2π`, after the IAU 1982 model.
+    pub fn gmst(&self) -> f64 {
+        let d = self.julian_date() - 2451545.0;
+        let t = d / 36525.0;
+        let deg =
+            280.46061837 + 360.98564736629 * d + 0.000387933 * t.powi(2) - t.powi(3) / 38710000.0;
+
+        let v = deg.to_radians() % core::f64::consts::TAU;
+        if v < 0.0 {
+            v + core::f64::consts::TAU
+        } else {
+            v
+        }
+    }
 }
 
 #[inline]
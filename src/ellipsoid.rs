@@ -0,0 +1,61 @@
+use crate::error::Error;
+
+/// Reference ellipsoid of geodetic coordinates, given by its equatorial radius in metres
+/// and its flattening.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ellipsoid {
+    a: f64,
+    f: f64,
+}
+
+impl Ellipsoid {
+    pub const WGS84: Ellipsoid = Ellipsoid {
+        a: 6378137.0,
+        f: 1.0 / 298.257223563,
+    };
+    pub const GRS80: Ellipsoid = Ellipsoid {
+        a: 6378137.0,
+        f: 1.0 / 298.257222101,
+    };
+    pub const CLARKE_1866: Ellipsoid = Ellipsoid {
+        a: 6378206.4,
+        f: 1.0 / 294.978698214,
+    };
+    pub const INTERNATIONAL_1924: Ellipsoid = Ellipsoid {
+        a: 6378388.0,
+        f: 1.0 / 297.0,
+    };
+
+    pub fn new(a: f64, f: f64) -> Result<Self, Error> {
+        if !(a.is_finite() && a > 0.0 && (0.0..1.0).contains(&f)) {
+            return Err(Error::InvalidEllipsoid { a, f });
+        }
+
+        Ok(Ellipsoid { a, f })
+    }
+
+    /// Equatorial radius in metres.
+    pub fn a(&self) -> f64 {
+        self.a
+    }
+
+    /// Polar radius in metres.
+    pub fn b(&self) -> f64 {
+        self.a * (1.0 - self.f)
+    }
+
+    pub fn f(&self) -> f64 {
+        self.f
+    }
+
+    /// Square of the first eccentricity.
+    pub fn e_2(&self) -> f64 {
+        self.f * (2.0 - self.f)
+    }
+}
+
+impl Default for Ellipsoid {
+    fn default() -> Self {
+        Ellipsoid::WGS84
+    }
+}
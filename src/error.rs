@@ -0,0 +1,136 @@
+use core::fmt;
+
+use crate::model::ParseError;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DateTimeField {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LocationField {
+    Longitude,
+    Latitude,
+    Height,
+    Radius,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// The model is not valid at the decimal year, which must lie in `start..=end`.
+    OutOfRange {
+        model: &'static str,
+        decimal: f64,
+        start: f64,
+        end: f64,
+    },
+    /// The model was not compiled into this build.
+    Unavailable {
+        model: &'static str,
+    },
+    /// The name does not match any release of the model.
+    UnknownRelease {
+        model: &'static str,
+    },
+    InvalidDateTime {
+        field: DateTimeField,
+        value: u32,
+    },
+    InvalidLocation {
+        field: LocationField,
+        value: f64,
+    },
+    /// An ellipsoid needs a positive equatorial radius and a flattening in `0..1`.
+    InvalidEllipsoid {
+        a: f64,
+        f: f64,
+    },
+    /// A span must have `start <= end` and a positive step.
+    InvalidSpan {
+        start: f64,
+        end: f64,
+        step: f64,
+    },
+    Parse(ParseError),
+}
+
+impl fmt::Display for DateTimeField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DateTimeField::Year => "year",
+            DateTimeField::Month => "month",
+            DateTimeField::Day => "day",
+            DateTimeField::Hour => "hour",
+            DateTimeField::Minute => "minute",
+            DateTimeField::Second => "second",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for LocationField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            LocationField::Longitude => "longitude",
+            LocationField::Latitude => "latitude",
+            LocationField::Height => "height",
+            LocationField::Radius => "radius",
+        };
+
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::OutOfRange {
+                model,
+                decimal,
+                start,
+                end,
+            } => write!(
+                f,
+                "{} is not valid at {}, expected {} to {}",
+                model, decimal, start, end
+            ),
+            Error::Unavailable { model } => write!(f, "{} is not available", model),
+            Error::UnknownRelease { model } => write!(f, "unknown {} release", model),
+            Error::InvalidDateTime { field, value } => {
+                write!(f, "invalid {}: {}", field, value)
+            }
+            Error::InvalidLocation { field, value } => {
+                write!(f, "invalid {}: {}", field, value)
+            }
+            Error::InvalidEllipsoid { a, f: flattening } => {
+                write!(f, "invalid ellipsoid: a = {}, f = {}", a, flattening)
+            }
+            Error::InvalidSpan { start, end, step } => {
+                write!(f, "invalid span: {} to {} by {}", start, end, step)
+            }
+            Error::Parse(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Self {
+        Error::Parse(e)
+    }
+}
+
+#[cfg(not(feature = "libm"))]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -1,3 +1,8 @@
+use crate::location::rotate_z;
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::zone::Zone;
+
 #[derive(Debug, Copy, Clone)]
 pub struct MagneticField {
     pub x: f64,
@@ -10,8 +15,142 @@ pub struct MagneticField {
     pub h_dot: f64,
     pub f: f64,
     pub f_dot: f64,
+    /// Declination in radians, positive east, in `-π..=π`.
     pub d: f64,
     pub d_dot: f64,
+    /// Inclination in radians, positive down, in `-π/2..=π/2`.
     pub i: f64,
     pub i_dot: f64,
+    pub zone: Zone,
+    /// Uncertainty of the model, where it publishes an error model.
+    pub uncertainty: Option<Uncertainty>,
+}
+
+/// One-sigma uncertainty of a field result, in nT for the intensities and in radians for
+/// the angles.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Uncertainty {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub h: f64,
+    pub f: f64,
+    pub d: f64,
+    pub i: f64,
+}
+
+impl MagneticField {
+    /// Derives the remaining elements from the north, east and down components in nT and
+    /// their rates in nT/year.
+    ///
+    /// Where `h` vanishes, as at the dip poles, the declination is taken as zero with no
+    /// rate of change, and `h_dot` is the rate at which `h` grows away from zero.
+    pub fn from_xyz(x: f64, y: f64, z: f64, x_dot: f64, y_dot: f64, z_dot: f64) -> Self {
+        let h = (x.powi(2) + y.powi(2)).sqrt();
+        let f = (h.powi(2) + z.powi(2)).sqrt();
+        let d = y.atan2(x);
+        let i = z.atan2(h);
+
+        let (h_dot, d_dot) = if h > 0.0 {
+            let h_dot = (x * x_dot + y * y_dot) / h;
+            let d_dot = (x * y_dot - y * x_dot) / h.powi(2);
+            (h_dot, d_dot)
+        } else {
+            ((x_dot.powi(2) + y_dot.powi(2)).sqrt(), 0.0)
+        };
+
+        let (f_dot, i_dot) = if f > 0.0 {
+            let f_dot = (x * x_dot + y * y_dot + z * z_dot) / f;
+            let i_dot = (h * z_dot - z * h_dot) / f.powi(2);
+            (f_dot, i_dot)
+        } else {
+            ((x_dot.powi(2) + y_dot.powi(2) + z_dot.powi(2)).sqrt(), 0.0)
+        };
+
+        MagneticField {
+            x,
+            x_dot,
+            y,
+            y_dot,
+            z,
+            z_dot,
+            h,
+            h_dot,
+            f,
+            f_dot,
+            d,
+            d_dot,
+            i,
+            i_dot,
+            zone: Zone::from_h(h),
+            uncertainty: None,
+        }
+    }
+}
+
+/// Field in geocentric spherical components, in nT with rates in nT/year.
+#[derive(Debug, Copy, Clone)]
+pub struct SphericalField {
+    /// Radial component, positive outward.
+    pub r: f64,
+    pub r_dot: f64,
+    /// Colatitude component, positive southward.
+    pub theta: f64,
+    pub theta_dot: f64,
+    /// Longitude component, positive eastward.
+    pub phi: f64,
+    pub phi_dot: f64,
+    /// Scalar potential in nT m, of which the field is the negative gradient.
+    pub v: f64,
+    pub v_dot: f64,
+}
+
+/// Spatial gradient of the north, east and down components, in nT/m with rates in
+/// nT/m/year.
+///
+/// `tensor[i][j]` is the derivative of component `i` along direction `j`, both ordered
+/// north, east, down. Outside the sources the tensor is symmetric and traceless.
+#[derive(Debug, Copy, Clone)]
+pub struct Gradient {
+    pub tensor: [[f64; 3]; 3],
+    pub tensor_dot: [[f64; 3]; 3],
+}
+
+/// Field as a Cartesian vector in an Earth-centred frame, in nT with rates in nT/year.
+///
+/// Rates are the secular variation only, without the rotation of the frame itself.
+#[derive(Debug, Copy, Clone)]
+pub struct CartesianField {
+    pub x: f64,
+    pub x_dot: f64,
+    pub y: f64,
+    pub y_dot: f64,
+    pub z: f64,
+    pub z_dot: f64,
+}
+
+impl CartesianField {
+    /// Rotates an Earth-fixed vector into the inertial frame at sidereal time `gmst`.
+    pub fn to_eci(&self, gmst: f64) -> Self {
+        self.rotate(gmst)
+    }
+
+    /// Rotates an inertial vector into the Earth-fixed frame at sidereal time `gmst`.
+    pub fn to_ecef(&self, gmst: f64) -> Self {
+        self.rotate(-gmst)
+    }
+
+    fn rotate(&self, angle: f64) -> Self {
+        let (x, y) = rotate_z(self.x, self.y, angle);
+        let (x_dot, y_dot) = rotate_z(self.x_dot, self.y_dot, angle);
+
+        CartesianField {
+            x,
+            x_dot,
+            y,
+            y_dot,
+            z: self.z,
+            z_dot: self.z_dot,
+        }
+    }
 }
@@ -0,0 +1,204 @@
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use crate::error::Error;
+use crate::model::{parse_field, ParseError, ParseErrorKind};
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::num::NumFrom;
+
+/// Bounds and spacing in degrees, and undulations in centimetres, of the 1° EGM96 grid
+/// embedded from `data/EGM96.I16`.
+static EGM96: ([f64; 6], &[i16]) = include!(concat!(env!("OUT_DIR"), "/EGM96_GRD"));
+
+/// Geoid undulations above the WGS84 ellipsoid on a regular latitude-longitude grid.
+///
+/// Grids are read in the `WW15MGH.GRD` layout distributed by NGA for EGM96: a first line
+/// with the south, north, west and east bounds and the latitude and longitude spacing in
+/// degrees, followed by undulations in metres row by row from north to south, each row
+/// from west to east including both edges. Values are kept to the centimetre.
+#[derive(Debug, Clone)]
+pub struct Geoid {
+    north: f64,
+    west: f64,
+    d_lat: f64,
+    d_lon: f64,
+    rows: usize,
+    cols: usize,
+    inner: Cow<'static, [i16]>,
+}
+
+impl Geoid {
+    /// EGM96 downsampled to 1°, embedded at build time.
+    pub fn egm96() -> Self {
+        let ([_, north, west, _, d_lat, d_lon], inner) = EGM96;
+
+        Geoid {
+            north,
+            west,
+            d_lat,
+            d_lon,
+            rows: 181,
+            cols: 361,
+            inner: Cow::Borrowed(inner),
+        }
+    }
+
+    #[cfg(not(feature = "libm"))]
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, ParseError> {
+        use std::io::{BufRead, BufReader};
+
+        let mut parser = Parser::default();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|e| ParseError::new(i + 1, ParseErrorKind::Io(e.kind())))?;
+            parser.push(i + 1, &line)?;
+        }
+
+        parser.finish()
+    }
+
+    /// Undulation in metres at a longitude and latitude in degrees, interpolated
+    /// bilinearly and clamped to the bounds of the grid. Outside a grid that does not go
+    /// round the globe, the nearer of its west and east edges is used.
+    pub fn undulation(&self, longitude: f64, latitude: f64) -> f64 {
+        let width = unsafe { f64::from_unchecked(self.cols - 1) } * self.d_lon;
+        let lon = (longitude - self.west) % 360.0;
+        let lon = if lon < 0.0 { lon + 360.0 } else { lon };
+        let lon = match lon > width && width < 360.0 {
+            true if lon - width < 360.0 - lon => width,
+            true => 0.0,
+            false => lon,
+        };
+        let x = (lon / self.d_lon).min(unsafe { f64::from_unchecked(self.cols - 1) });
+        let y = ((self.north - latitude) / self.d_lat)
+            .clamp(0.0, unsafe { f64::from_unchecked(self.rows - 1) });
+
+        let j = unsafe { usize::from_unchecked(x.floor()) }.min(self.cols.saturating_sub(2));
+        let i = unsafe { usize::from_unchecked(y.floor()) }.min(self.rows.saturating_sub(2));
+        let tx = x - unsafe { f64::from_unchecked(j) };
+        let ty = y - unsafe { f64::from_unchecked(i) };
+
+        let at = |i: usize, j: usize| {
+            let i = i.min(self.rows - 1);
+            let j = j.min(self.cols - 1);
+            f64::from(self.inner[i * self.cols + j]) / 100.0
+        };
+
+        let top = at(i, j) * (1.0 - tx) + at(i, j + 1) * tx;
+        let bottom = at(i + 1, j) * (1.0 - tx) + at(i + 1, j + 1) * tx;
+        top * (1.0 - ty) + bottom * ty
+    }
+}
+
+impl FromStr for Geoid {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::default();
+        for (i, line) in s.lines().enumerate() {
+            parser.push(i + 1, line)?;
+        }
+
+        parser.finish()
+    }
+}
+
+/// Rows and columns of a grid, if the bounds are ordered and divisible by the spacing.
+fn size(
+    south: f64,
+    north: f64,
+    west: f64,
+    east: f64,
+    d_lat: f64,
+    d_lon: f64,
+) -> Option<(usize, usize)> {
+    let count = |start: f64, end: f64, step: f64| {
+        let v = (end - start) / step;
+        let valid = start < end && step > 0.0 && (-1e-6..1e-6).contains(&(v - (v + 0.5).floor()));
+        valid.then(|| unsafe { usize::from_unchecked((v + 0.5).floor()) } + 1)
+    };
+
+    if east - west > 360.0 || south < -90.0 || north > 90.0 {
+        return None;
+    }
+
+    Some((count(south, north, d_lat)?, count(west, east, d_lon)?))
+}
+
+/// Rounds to the nearest centimetre, halves away from zero as `build.rs` does for the
+/// embedded grid.
+fn centimetres(metres: f64) -> Option<i16> {
+    let cm = (metres * 100.0).round();
+    (f64::from(i16::MIN)..=f64::from(i16::MAX))
+        .contains(&cm)
+        .then(|| unsafe { i16::from_unchecked(cm) })
+}
+
+#[derive(Default)]
+struct Parser {
+    line: usize,
+    header: Option<[f64; 6]>,
+    inner: Vec<i16>,
+}
+
+impl Parser {
+    fn push(&mut self, line: usize, content: &str) -> Result<(), ParseError> {
+        self.line = line;
+
+        let content = content.trim();
+        if content.is_empty() {
+            return Ok(());
+        }
+
+        let mut fields = content.split_whitespace();
+        if self.header.is_none() {
+            let mut header = [0.0; 6];
+            for v in header.iter_mut() {
+                *v = parse_field(&mut fields, line)
+                    .map_err(|_| ParseError::new(line, ParseErrorKind::InvalidHeader))?;
+            }
+
+            self.header = Some(header);
+            return Ok(());
+        }
+
+        for value in fields {
+            let metres: f64 = value
+                .parse()
+                .map_err(|_| ParseError::new(line, ParseErrorKind::InvalidNumber))?;
+            let cm =
+                centimetres(metres).ok_or(ParseError::new(line, ParseErrorKind::InvalidNumber))?;
+            self.inner.push(cm);
+        }
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Geoid, ParseError> {
+        let Some([south, north, west, east, d_lat, d_lon]) = self.header else {
+            return Err(ParseError::new(self.line, ParseErrorKind::Empty));
+        };
+
+        let (rows, cols) = size(south, north, west, east, d_lat, d_lon)
+            .ok_or(ParseError::new(1, ParseErrorKind::InvalidHeader))?;
+
+        if self.inner.len() < rows * cols {
+            return Err(ParseError::new(self.line, ParseErrorKind::MissingField));
+        }
+
+        if self.inner.len() > rows * cols {
+            return Err(ParseError::new(1, ParseErrorKind::InvalidHeader));
+        }
+
+        Ok(Geoid {
+            north,
+            west,
+            d_lat,
+            d_lon,
+            rows,
+            cols,
+            inner: Cow::Owned(self.inner),
+        })
+    }
+}
@@ -0,0 +1,118 @@
+use crate::ellipsoid::Ellipsoid;
+use crate::error::Error;
+use crate::location::GeodeticLocation;
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::num::NumFrom;
+
+/// Evenly spaced values from `start` to `end` inclusive.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Span {
+    pub start: f64,
+    pub end: f64,
+    pub step: f64,
+}
+
+impl Span {
+    pub fn new(start: f64, end: f64, step: f64) -> Self {
+        Span { start, end, step }
+    }
+
+    pub fn single(value: f64) -> Self {
+        Span::new(value, value, 1.0)
+    }
+
+    #[inline]
+    fn is_valid(&self) -> bool {
+        self.start.is_finite()
+            && self.end.is_finite()
+            && self.step.is_finite()
+            && self.start <= self.end
+            && self.step > 0.0
+    }
+
+    /// Fails with [`Error::InvalidSpan`] unless `start <= end` and the step is positive.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !self.is_valid() {
+            return Err(Error::InvalidSpan {
+                start: self.start,
+                end: self.end,
+                step: self.step,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Number of values, zero if the span is not valid.
+    pub fn len(&self) -> usize {
+        if !self.is_valid() {
+            return 0;
+        }
+
+        let v = ((self.end - self.start) / self.step + 1e-9).floor();
+        unsafe { usize::from_unchecked(v) }.saturating_add(1)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, i: usize) -> f64 {
+        self.start + unsafe { f64::from_unchecked(i) } * self.step
+    }
+}
+
+/// Points spanned by latitude and longitude in degrees and height in metres, on the
+/// WGS84 ellipsoid unless `ellipsoid` is changed.
+///
+/// Points are ordered by height, then latitude, then longitude, so that longitude varies
+/// fastest.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Grid {
+    pub latitude: Span,
+    pub longitude: Span,
+    pub height: Span,
+    pub ellipsoid: Ellipsoid,
+}
+
+impl Grid {
+    pub fn new(latitude: Span, longitude: Span, height: Span) -> Result<Self, Error> {
+        for span in [latitude, longitude, height] {
+            span.validate()?;
+        }
+
+        GeodeticLocation::new(longitude.start, latitude.start, height.start)?;
+        GeodeticLocation::new(longitude.end, latitude.end, height.end)?;
+
+        Ok(Grid {
+            latitude,
+            longitude,
+            height,
+            ellipsoid: Ellipsoid::WGS84,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.latitude
+            .len()
+            .saturating_mul(self.longitude.len())
+            .saturating_mul(self.height.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn location(&self, i: usize) -> GeodeticLocation {
+        let lon = self.longitude.len();
+        let lat = self.latitude.len();
+
+        GeodeticLocation {
+            longitude: self.longitude.get(i % lon).to_radians(),
+            latitude: self.latitude.get(i / lon % lat).to_radians(),
+            height: self.height.get(i / lon / lat),
+            ellipsoid: self.ellipsoid,
+        }
+    }
+}
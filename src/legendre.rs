@@ -0,0 +1,167 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::num::NumFrom;
+
+/// Second-order terms of [`Legendre`], computed only when needed.
+pub(crate) struct Second {
+    ddp: Vec<f64>,
+    dpu: Vec<f64>,
+}
+
+/// Scale applied to the column recursion so that `u^m` can underflow on its own without
+/// losing terms that grow back to a representable magnitude at higher degrees.
+const SCALE: f64 = 1e-280;
+
+#[inline]
+fn index(n: usize, m: usize) -> usize {
+    n * (n + 1) / 2 + m
+}
+
+#[inline]
+fn roots(deg: usize) -> Vec<f64> {
+    (0..=2 * deg + 2)
+        .map(|i| unsafe { f64::from_unchecked(i) }.sqrt())
+        .collect()
+}
+
+/// Applies `dP(n, m) = (c P(n, m - 1) - d P(n, m + 1)) / 2` to any functions laid out as
+/// `P`, so that derivatives of derivatives stay regular at the poles.
+fn derivative(deg: usize, roots: &[f64], f: &[f64]) -> Vec<f64> {
+    let mut df = vec![0.0; f.len()];
+
+    for n in 1..=deg {
+        let f_at = |m: usize| if m <= n { f[index(n, m)] } else { 0.0 };
+
+        df[index(n, 0)] = -roots[n] * roots[n + 1] / roots[2] * f_at(1);
+
+        for m in 1..=n {
+            let lower = roots[n + m] * roots[n - m + 1] * f_at(m - 1);
+            let upper = roots[n + m + 1] * roots[n - m] * f_at(m + 1);
+            let lower = if m == 1 { roots[2] * lower } else { lower };
+
+            df[index(n, m)] = 0.5 * (lower - upper);
+        }
+    }
+
+    df
+}
+
+///## References
+///* [Holmes, S. A., Featherstone, W. E., “A unified approach to the Clenshaw summation and the recursive computation of very high degree and order normalised associated Legendre functions”, Journal of Geodesy, 76, 279–299, 2002](https://doi.org/10.1007/s00190-002-0216-2)
+///
+/// Schmidt semi-normalized associated Legendre functions `P(n, m)` of `cos θ` and their
+/// derivatives with respect to the colatitude `θ`, for all `0 <= m <= n <= deg`.
+///
+/// `P(n, m) / sin θ` is kept as well for `m >= 1`. Since `P(n, m)` carries a factor of
+/// `sin^m θ`, it is evaluated without the division and has the exact pole limit.
+pub(crate) struct Legendre {
+    deg: usize,
+    p: Vec<f64>,
+    dp: Vec<f64>,
+    pu: Vec<f64>,
+}
+
+impl Legendre {
+    /// `x = cos θ` and `u = sin θ` of the colatitude `θ`.
+    pub(crate) fn new(deg: usize, x: f64, u: f64) -> Self {
+        let size = index(deg, deg) + 1;
+        let mut p = vec![0.0; size];
+        let mut pu = vec![0.0; size];
+
+        let roots = roots(deg);
+
+        // Sectoral seed without its u^m factor, and u^m carried separately.
+        let mut pmm = SCALE;
+        let mut um = 1.0 / SCALE;
+        let mut um1 = 0.0;
+
+        for m in 0..=deg {
+            if m > 1 {
+                pmm *= roots[2 * m - 1] / roots[2 * m];
+            }
+
+            let mut p2 = 0.0;
+            let mut p1 = pmm;
+            p[index(m, m)] = p1 * um;
+            pu[index(m, m)] = p1 * um1;
+
+            for n in (m + 1)..=deg {
+                let n_f = unsafe { f64::from_unchecked(n) };
+                let a = (2.0 * n_f - 1.0) * x * p1;
+                let b = roots[n - 1 - m] * roots[n - 1 + m] * p2;
+                let pnm = (a - b) / (roots[n - m] * roots[n + m]);
+
+                p[index(n, m)] = pnm * um;
+                pu[index(n, m)] = pnm * um1;
+                p2 = p1;
+                p1 = pnm;
+            }
+
+            um1 = um;
+            um *= u;
+        }
+
+        let dp = derivative(deg, &roots, &p);
+
+        Legendre { deg, p, dp, pu }
+    }
+
+    /// Second derivatives with respect to the colatitude, and derivatives of
+    /// `P(n, m) / sin θ`, both free of any division by `sin θ`.
+    pub(crate) fn second(&self) -> Second {
+        let deg = self.deg;
+        let roots = roots(deg);
+        let ddp = derivative(deg, &roots, &self.dp);
+
+        // P(n, m) / sin θ as a combination of degree n - 1 at orders m - 1 and m + 1.
+        let mut dpu = vec![0.0; ddp.len()];
+        for n in 2..=deg {
+            let dp_at = |m: usize| if m < n { self.dp[index(n - 1, m)] } else { 0.0 };
+
+            for m in 1..=n {
+                let m_f = unsafe { f64::from_unchecked(m) };
+                let upper = roots[n - m] * roots[(n - m).saturating_sub(1)] * dp_at(m + 1);
+                let lower = roots[n + m] * roots[n + m - 1] * dp_at(m - 1);
+                let lower = if m == 1 { roots[2] * lower } else { lower };
+
+                dpu[index(n, m)] = (upper + lower) / (2.0 * m_f);
+            }
+        }
+
+        Second { ddp, dpu }
+    }
+
+    #[inline]
+    pub(crate) fn p(&self, n: usize, m: usize) -> f64 {
+        self.p[index(n, m)]
+    }
+
+    /// `P(n, m) / sin θ`, only defined for `m >= 1`.
+    #[inline]
+    pub(crate) fn pu(&self, n: usize, m: usize) -> f64 {
+        self.pu[index(n, m)]
+    }
+
+    /// Derivative with respect to the colatitude.
+    #[inline]
+    pub(crate) fn dp(&self, n: usize, m: usize) -> f64 {
+        self.dp[index(n, m)]
+    }
+}
+
+impl Second {
+    /// Second derivative with respect to the colatitude.
+    #[inline]
+    pub(crate) fn ddp(&self, n: usize, m: usize) -> f64 {
+        self.ddp[index(n, m)]
+    }
+
+    /// Derivative of `P(n, m) / sin θ` with respect to the colatitude, for `m >= 1`.
+    #[inline]
+    pub(crate) fn dpu(&self, n: usize, m: usize) -> f64 {
+        self.dpu[index(n, m)]
+    }
+}
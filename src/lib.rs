@@ -1,25 +1,48 @@
 #![allow(unused_imports)]
 #![cfg_attr(feature = "libm", no_std)]
 
+extern crate alloc;
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
 pub use crate::datetime::DateTime;
-pub use crate::field::MagneticField;
-pub use crate::location::GeodeticLocation;
+pub use crate::ellipsoid::Ellipsoid;
+pub use crate::error::{DateTimeField, Error, LocationField};
+pub use crate::field::{CartesianField, Gradient, MagneticField, SphericalField, Uncertainty};
+#[cfg(feature = "geoid")]
+pub use crate::geoid::Geoid;
+pub use crate::grid::{Grid, Span};
+pub use crate::location::{EcefLocation, EciLocation, GeocentricLocation, GeodeticLocation};
+#[cfg(feature = "wmm")]
+pub use crate::model::Cof;
 #[cfg(feature = "igrf")]
 pub use crate::model::IGRF;
+pub use crate::model::{Model, ParseError, ParseErrorKind, Shc, ShcModel};
 #[cfg(feature = "wmm")]
-pub use crate::model::WMM;
+pub use crate::model::{WmmRelease, WMM};
+pub use crate::zone::{Hemisphere, Zone, ZoneBoundaries};
 
-use crate::location::GeocentricLocation;
-use crate::model::{Gauss, Model};
-use crate::num::{Float, NumFrom};
-use crate::polynomial::lpmv;
+use crate::legendre::Legendre;
+use crate::model::{Gauss, Sealed};
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::num::NumFrom;
 
 mod datetime;
+mod ellipsoid;
+mod error;
 mod field;
+#[cfg(feature = "geoid")]
+mod geoid;
+mod grid;
+mod legendre;
 mod location;
 mod model;
+pub mod navigation;
 mod num;
-mod polynomial;
+mod spline;
+mod zone;
 
 #[derive(Default)]
 pub(crate) struct Vector {
@@ -29,141 +52,305 @@ pub(crate) struct Vector {
     dx: f64,
     dy: f64,
     dz: f64,
+    v: f64,
+    dv: f64,
 }
 
 impl From<Vector> for MagneticField {
     fn from(v: Vector) -> Self {
-        let h = (v.x.powi(2) + v.y.powi(2)).sqrt();
-        let f = (h.powi(2) + v.z.powi(2)).sqrt();
-        let d = (v.y / v.x).atan();
-        let i = (v.z / h).atan();
-
-        let dh = (v.x * v.dx + v.y * v.dy) / h;
-        let df = (v.x * v.dx + v.y * v.dy + v.z * v.dz) / f;
-        let dd = (v.x * v.dy - v.y * v.dx) / h.powi(2);
-        let di = (h * v.dz - v.z * dh) / f.powi(2);
+        MagneticField::from_xyz(v.x, v.y, v.z, v.dx, v.dy, v.dz)
+    }
+}
 
-        MagneticField {
+impl From<Vector> for CartesianField {
+    fn from(v: Vector) -> Self {
+        CartesianField {
             x: v.x,
             x_dot: v.dx,
             y: v.y,
             y_dot: v.dy,
             z: v.z,
             z_dot: v.dz,
-            h,
-            h_dot: dh,
-            f,
-            f_dot: df,
-            d,
-            d_dot: dd,
-            i,
-            i_dot: di,
         }
     }
 }
 
-pub(crate) struct Calculator<'a, T>
-where
-    T: Gauss,
-{
+impl From<Vector> for SphericalField {
+    /// Expects the geocentric north, east and down components.
+    fn from(v: Vector) -> Self {
+        SphericalField {
+            r: -v.z,
+            r_dot: -v.dz,
+            theta: -v.x,
+            theta_dot: -v.dx,
+            phi: v.y,
+            phi_dot: v.dy,
+            v: v.v,
+            v_dot: v.dv,
+        }
+    }
+}
+
+/// Completes a field result with what the model knows about it.
+#[inline]
+fn magnetic_field<T: Model + ?Sized>(model: &T, v: Vector) -> MagneticField {
+    let mut field = MagneticField::from(v);
+    field.uncertainty = model.uncertainty(field.h);
+    field
+}
+
+#[inline]
+fn nm_to_index(n: usize, m: usize) -> usize {
+    n * (n + 1) / 2 + m - 1
+}
+
+/// Terms that depend only on the latitude and height of a point.
+pub(crate) struct Parallel {
+    legendre: Legendre,
+    radius: f64,
+    radial: Vec<f64>,
+    sin_p: f64,
+    cos_p: f64,
+}
+
+/// Terms that depend only on the longitude of a point.
+pub(crate) struct Meridian {
+    cos_ml: Vec<f64>,
+    sin_ml: Vec<f64>,
+}
+
+pub(crate) struct Calculator<'a> {
     deg: usize,
-    gauss: &'a T,
-    geocentric: &'a GeocentricLocation,
-    geodetic: &'a GeodeticLocation,
+    gauss: Cow<'a, [[f64; 4]]>,
 }
 
-impl<'a, T: Gauss> Calculator<'a, T> {
-    pub(crate) fn new(
-        deg: usize,
-        gauss: &'a T,
-        geocentric: &'a GeocentricLocation,
-        geodetic: &'a GeodeticLocation,
-    ) -> Self {
-        Calculator {
-            deg,
-            gauss,
-            geocentric,
-            geodetic,
-        }
+impl<'a> Calculator<'a> {
+    /// Borrows the coefficients of models that store them adjusted, and combines them
+    /// once otherwise.
+    pub(crate) fn new<T: Model + ?Sized>(model: &'a T) -> Self {
+        let deg = model.deg();
+        let gauss = match model.adjusted(Sealed::TOKEN) {
+            Some(adjusted) => Cow::Borrowed(adjusted),
+            None => {
+                let mut inner = Vec::with_capacity(nm_to_index(deg, deg) + 1);
+                for n in 1..=deg {
+                    for m in 0..=n {
+                        let g = Gauss::g(model, n, m);
+                        let h = Gauss::h(model, n, m);
+                        inner.push([g, h, model.dg(n, m), model.dh(n, m)]);
+                    }
+                }
+                Cow::Owned(inner)
+            }
+        };
+
+        Calculator { deg, gauss }
     }
 
-    #[inline]
-    unsafe fn lpmn(&self, n: usize, m: usize, z: f64) -> f64 {
-        let m_f = f64::from_unchecked(m);
-        let pnm = (-1.0_f64).powf(m_f) * lpmv(n, m, z);
+    /// Rotates the result from the geocentric frame to the frame at `latitude`.
+    pub(crate) fn parallel(&self, geocentric: &GeocentricLocation, latitude: f64) -> Parallel {
+        let r = geocentric.radius;
+        let p = geocentric.latitude;
+        let a = 6371200.0_f64;
 
-        if m > 0 {
-            let mut d = 1.0;
-            for i in (n - m + 1)..=(n + m) {
-                d *= f64::from_unchecked(i);
-            }
+        let legendre = Legendre::new(self.deg, p.sin(), p.cos());
 
-            pnm * (2.0 * (1.0 / d)).sqrt()
-        } else {
-            pnm
+        let mut radial = Vec::with_capacity(self.deg + 1);
+        let mut f = (a / r).powi(2);
+        for _ in 0..=self.deg {
+            radial.push(f);
+            f *= a / r;
+        }
+
+        let p1 = geocentric.latitude;
+        let p = latitude;
+
+        Parallel {
+            legendre,
+            radius: r,
+            radial,
+            sin_p: (p1 - p).sin(),
+            cos_p: (p1 - p).cos(),
         }
     }
 
-    unsafe fn xyz_prime(&self) -> Vector {
-        let mut prime = Vector::default();
+    pub(crate) fn meridian(&self, longitude: f64) -> Meridian {
+        let (cos_ml, sin_ml) = (0..=self.deg)
+            .map(|m| {
+                let ml = unsafe { f64::from_unchecked(m) } * longitude;
+                (ml.cos(), ml.sin())
+            })
+            .unzip();
 
-        let r = self.geocentric.radius;
-        let p = self.geocentric.latitude;
-        let l = self.geocentric.longitude;
-        let a = 6371200.0_f64;
+        Meridian { cos_ml, sin_ml }
+    }
 
-        let sin_p = p.sin();
-        let cos_p = p.cos();
+    unsafe fn xyz_prime(&self, parallel: &Parallel, meridian: &Meridian) -> Vector {
+        let mut prime = Vector::default();
+
+        let legendre = &parallel.legendre;
+        let cos_ml = &meridian.cos_ml;
+        let sin_ml = &meridian.sin_ml;
 
         for n in 1..=self.deg {
             let n_f = f64::from_unchecked(n);
-            let f = (a / r).powf(n_f + 2.0);
+            let f = parallel.radial[n];
 
             for m in 0..=n {
                 let m_f = f64::from_unchecked(m);
-                let cos_ml = (m_f * l).cos();
-                let sin_ml = (m_f * l).sin();
-
-                let pmn = self.lpmn(n, m, sin_p);
-                let pmn1 = self.lpmn(n + 1, m, sin_p);
-                let dmn = (n_f + 1.0) * p.tan() * pmn
-                    - ((n_f + 1.0).powi(2) - m_f.powi(2)).sqrt() / p.cos() * pmn1;
-
-                let g = self.gauss.g(n, m);
-                let h = self.gauss.h(n, m);
-                let g_cos_ml = g * cos_ml;
-                let g_sin_ml = g * sin_ml;
-                let h_cos_ml = h * cos_ml;
-                let h_sin_ml = h * sin_ml;
-
-                prime.x += -f * (g_cos_ml + h_sin_ml) * dmn;
-                prime.y += (f / cos_p) * m_f * (g_sin_ml - h_cos_ml) * pmn;
+
+                let pmn = legendre.p(n, m);
+                let dmn = legendre.dp(n, m);
+                let umn = if m > 0 { legendre.pu(n, m) } else { 0.0 };
+
+                let [g, h, dg, dh] = self.gauss[nm_to_index(n, m)];
+                let g_cos_ml = g * cos_ml[m];
+                let g_sin_ml = g * sin_ml[m];
+                let h_cos_ml = h * cos_ml[m];
+                let h_sin_ml = h * sin_ml[m];
+
+                prime.x += f * (g_cos_ml + h_sin_ml) * dmn;
+                prime.y += f * m_f * (g_sin_ml - h_cos_ml) * umn;
                 prime.z += -f * (n_f + 1.0) * (g_cos_ml + h_sin_ml) * pmn;
 
-                let dg = self.gauss.dg(n, m);
-                let dh = self.gauss.dh(n, m);
-                let dg_cos_ml = dg * cos_ml;
-                let dg_sin_ml = dg * sin_ml;
-                let dh_cos_ml = dh * cos_ml;
-                let dh_sin_ml = dh * sin_ml;
+                let dg_cos_ml = dg * cos_ml[m];
+                let dg_sin_ml = dg * sin_ml[m];
+                let dh_cos_ml = dh * cos_ml[m];
+                let dh_sin_ml = dh * sin_ml[m];
 
-                prime.dx += (-f) * (dg_cos_ml + dh_sin_ml) * dmn;
-                prime.dy += (f / cos_p) * m_f * (dg_sin_ml - dh_cos_ml) * pmn;
+                prime.dx += f * (dg_cos_ml + dh_sin_ml) * dmn;
+                prime.dy += f * m_f * (dg_sin_ml - dh_cos_ml) * umn;
                 prime.dz += -f * (n_f + 1.0) * (dg_cos_ml + dh_sin_ml) * pmn;
+
+                prime.v += f * (g_cos_ml + h_sin_ml) * pmn;
+                prime.dv += f * (dg_cos_ml + dh_sin_ml) * pmn;
             }
         }
 
+        prime.v *= parallel.radius;
+        prime.dv *= parallel.radius;
+
         prime
     }
 
-    pub(crate) fn xyz(&self) -> Vector {
+    /// Geocentric north, east and down components, with the potential.
+    pub(crate) fn spherical(&self, parallel: &Parallel, meridian: &Meridian) -> Vector {
+        unsafe { self.xyz_prime(parallel, meridian) }
+    }
+
+    /// Rotates the geocentric north, east and down components into the Earth-fixed frame.
+    pub(crate) fn ecef(
+        &self,
+        parallel: &Parallel,
+        meridian: &Meridian,
+        geocentric: &GeocentricLocation,
+    ) -> Vector {
+        let prime = unsafe { self.xyz_prime(parallel, meridian) };
+
+        let (sin_p, cos_p) = (geocentric.latitude.sin(), geocentric.latitude.cos());
+        let (sin_l, cos_l) = (geocentric.longitude.sin(), geocentric.longitude.cos());
+        let rotate = |n: f64, e: f64, d: f64| {
+            let horizontal = -n * sin_p - d * cos_p;
+            (
+                horizontal * cos_l - e * sin_l,
+                horizontal * sin_l + e * cos_l,
+                n * cos_p - d * sin_p,
+            )
+        };
+
+        let (x, y, z) = rotate(prime.x, prime.y, prime.z);
+        let (dx, dy, dz) = rotate(prime.dx, prime.dy, prime.dz);
+
+        Vector {
+            x,
+            y,
+            z,
+            dx,
+            dy,
+            dz,
+            ..prime
+        }
+    }
+
+    /// Second derivatives of the potential in the geocentric frame, as
+    /// `[rr, θθ, rθ, rφ, θφ]` each multiplied by `r`, for the coefficients and their rates.
+    unsafe fn hessian_prime(&self, parallel: &Parallel, meridian: &Meridian) -> [[f64; 5]; 2] {
+        let mut hessian = [[0.0; 5]; 2];
+
+        let legendre = &parallel.legendre;
+        let second = legendre.second();
+        let cos_ml = &meridian.cos_ml;
+        let sin_ml = &meridian.sin_ml;
+
+        for n in 1..=self.deg {
+            let n_f = f64::from_unchecked(n);
+            let f = parallel.radial[n];
+
+            for m in 0..=n {
+                let m_f = f64::from_unchecked(m);
+
+                let pmn = legendre.p(n, m);
+                let dmn = legendre.dp(n, m);
+                let ddmn = second.ddp(n, m);
+                let (umn, dumn) = if m > 0 {
+                    (legendre.pu(n, m), second.dpu(n, m))
+                } else {
+                    (0.0, 0.0)
+                };
+
+                let [g, h, dg, dh] = self.gauss[nm_to_index(n, m)];
+                for (sum, [g, h]) in hessian.iter_mut().zip([[g, h], [dg, dh]]) {
+                    let c = g * cos_ml[m] + h * sin_ml[m];
+                    let s = g * sin_ml[m] - h * cos_ml[m];
+
+                    sum[0] += f * (n_f + 1.0) * (n_f + 2.0) * c * pmn;
+                    sum[1] += f * c * (ddmn - (n_f + 1.0) * pmn);
+                    sum[2] += -f * (n_f + 2.0) * c * dmn;
+                    sum[3] += f * (n_f + 2.0) * m_f * s * umn;
+                    sum[4] += -f * m_f * s * dumn;
+                }
+            }
+        }
+
+        hessian
+    }
+
+    /// Gradient of the north, east and down components, rotated like [`Calculator::xyz`].
+    pub(crate) fn gradient(&self, parallel: &Parallel, meridian: &Meridian) -> Gradient {
+        let hessian = unsafe { self.hessian_prime(parallel, meridian) };
+        let r = parallel.radius;
+        let (sin_p, cos_p) = (parallel.sin_p, parallel.cos_p);
+
+        let [tensor, tensor_dot] = hessian.map(|[rr, tt, rt, rp, tp]| {
+            let (rr, tt, rt, rp, tp) = (rr / r, tt / r, rt / r, rp / r, tp / r);
+            let pp = -rr - tt;
+
+            let prime = [[-tt, tp, -rt], [tp, -pp, rp], [-rt, rp, -rr]];
+            let rotation = [[cos_p, 0.0, -sin_p], [0.0, 1.0, 0.0], [sin_p, 0.0, cos_p]];
+
+            let mut tensor = [[0.0; 3]; 3];
+            for (i, row) in tensor.iter_mut().enumerate() {
+                for (j, t) in row.iter_mut().enumerate() {
+                    for (k, prime_k) in prime.iter().enumerate() {
+                        for (l, prime_kl) in prime_k.iter().enumerate() {
+                            *t += rotation[i][k] * prime_kl * rotation[j][l];
+                        }
+                    }
+                }
+            }
+
+            tensor
+        });
+
+        Gradient { tensor, tensor_dot }
+    }
+
+    pub(crate) fn xyz(&self, parallel: &Parallel, meridian: &Meridian) -> Vector {
         let mut xyz = Vector::default();
-        let prime = unsafe { self.xyz_prime() };
+        let prime = unsafe { self.xyz_prime(parallel, meridian) };
 
-        let p1 = self.geocentric.latitude;
-        let p = self.geodetic.latitude;
-        let sin_p = (p1 - p).sin();
-        let cos_p = (p1 - p).cos();
+        let sin_p = parallel.sin_p;
+        let cos_p = parallel.cos_p;
 
         xyz.x = prime.x * cos_p - prime.z * sin_p;
         xyz.y = prime.y;
@@ -177,18 +364,165 @@ impl<'a, T: Gauss> Calculator<'a, T> {
     }
 }
 
-pub trait Geomag {
+/// Field evaluation, implemented for references to every [`Model`].
+pub trait Geomag: Sized {
     fn at_location(self, geodetic: &GeodeticLocation) -> MagneticField;
+
+    /// Spatial gradient of the field at a geodetic location.
+    fn gradient_at_location(self, geodetic: &GeodeticLocation) -> Gradient;
+
+    /// Spherical components and potential at a geocentric location.
+    fn at_geocentric(self, geocentric: &GeocentricLocation) -> SphericalField;
+
+    /// Field vector in the Earth-fixed frame.
+    fn at_ecef(self, ecef: &EcefLocation) -> CartesianField;
+
+    /// Field vector in the inertial frame, at sidereal time `gmst` in radians as given by
+    /// [`DateTime::gmst`].
+    fn at_eci(self, eci: &EciLocation, gmst: f64) -> CartesianField {
+        self.at_ecef(&eci.to_ecef(gmst)).to_eci(gmst)
+    }
+
+    /// Evaluates every point of the grid, computing the Legendre functions once per
+    /// latitude and height, and the longitude terms once per longitude.
+    fn at_grid_map<U, F>(self, grid: &Grid, f: F) -> Vec<U>
+    where
+        F: FnMut(MagneticField) -> U;
+
+    fn at_grid(self, grid: &Grid) -> Vec<MagneticField> {
+        self.at_grid_map(grid, |m| m)
+    }
+
+    /// Locates the dip pole of a hemisphere at `height` in metres by minimising `h`, and
+    /// bisects the blackout and caution zone boundaries along `bearings` evenly spaced
+    /// bearings from it.
+    fn zone_boundaries(
+        self,
+        hemisphere: Hemisphere,
+        height: f64,
+        bearings: usize,
+    ) -> Result<ZoneBoundaries, Error>
+    where
+        Self: Copy,
+    {
+        zone::boundaries(self, hemisphere, height, bearings)
+    }
+}
+
+/// Models that can be moved to another time, reusing the coefficients they hold.
+pub trait TimeVarying: Model + Sized {
+    /// The same model evaluated at `decimal`.
+    fn at_time(&self, decimal: f64) -> Result<Self, Error>;
+
+    fn at(&self, geodetic: &GeodeticLocation, decimal: f64) -> Result<MagneticField, Error> {
+        Ok(self.at_time(decimal)?.at_location(geodetic))
+    }
+
+    /// Evaluates one location at every time of the span, computing the terms that depend
+    /// on the location only once.
+    fn time_series(
+        &self,
+        geodetic: &GeodeticLocation,
+        times: Span,
+    ) -> Result<Vec<MagneticField>, Error> {
+        times.validate()?;
+
+        let models = (0..times.len())
+            .map(|i| self.at_time(times.get(i)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let deepest = models.iter().max_by_key(|m| m.deg()).unwrap_or(self);
+
+        let geocentric = GeocentricLocation::from(geodetic);
+        let mag = Calculator::new(deepest);
+        let parallel = mag.parallel(&geocentric, geodetic.latitude);
+        let meridian = mag.meridian(geocentric.longitude);
+
+        let fields = models
+            .iter()
+            .map(|m| magnetic_field(m, Calculator::new(m).xyz(&parallel, &meridian)))
+            .collect();
+
+        Ok(fields)
+    }
+}
+
+#[cfg(feature = "igrf")]
+impl TimeVarying for IGRF {
+    fn at_time(&self, decimal: f64) -> Result<Self, Error> {
+        IGRF::at_time(self, decimal)
+    }
+}
+
+#[cfg(feature = "wmm")]
+impl TimeVarying for WMM {
+    fn at_time(&self, decimal: f64) -> Result<Self, Error> {
+        WMM::at_time(self, decimal)
+    }
 }
 
 impl<T> Geomag for &T
 where
-    T: Model,
+    T: Model + ?Sized,
 {
     fn at_location(self, geodetic: &GeodeticLocation) -> MagneticField {
         let geocentric = GeocentricLocation::from(geodetic);
-        let mag = Calculator::new(self.deg(), self, &geocentric, geodetic);
-        let xyz = mag.xyz();
-        xyz.into()
+        let mag = Calculator::new(self);
+        let parallel = mag.parallel(&geocentric, geodetic.latitude);
+        let meridian = mag.meridian(geocentric.longitude);
+        magnetic_field(self, mag.xyz(&parallel, &meridian))
+    }
+
+    fn gradient_at_location(self, geodetic: &GeodeticLocation) -> Gradient {
+        let geocentric = GeocentricLocation::from(geodetic);
+        let mag = Calculator::new(self);
+        let parallel = mag.parallel(&geocentric, geodetic.latitude);
+        let meridian = mag.meridian(geocentric.longitude);
+        mag.gradient(&parallel, &meridian)
+    }
+
+    fn at_geocentric(self, geocentric: &GeocentricLocation) -> SphericalField {
+        let mag = Calculator::new(self);
+        let parallel = mag.parallel(geocentric, geocentric.latitude);
+        let meridian = mag.meridian(geocentric.longitude);
+        mag.spherical(&parallel, &meridian).into()
+    }
+
+    fn at_ecef(self, ecef: &EcefLocation) -> CartesianField {
+        let geocentric = GeocentricLocation::from(ecef);
+        let mag = Calculator::new(self);
+        let parallel = mag.parallel(&geocentric, geocentric.latitude);
+        let meridian = mag.meridian(geocentric.longitude);
+        mag.ecef(&parallel, &meridian, &geocentric).into()
+    }
+
+    fn at_grid_map<U, F>(self, grid: &Grid, mut f: F) -> Vec<U>
+    where
+        F: FnMut(MagneticField) -> U,
+    {
+        let mag = Calculator::new(self);
+        let meridians: Vec<Meridian> = (0..grid.longitude.len())
+            .map(|j| mag.meridian(grid.longitude.get(j).to_radians()))
+            .collect();
+
+        let mut fields = Vec::with_capacity(grid.len());
+        for k in 0..grid.height.len() {
+            for i in 0..grid.latitude.len() {
+                let geodetic = GeodeticLocation {
+                    longitude: 0.0,
+                    latitude: grid.latitude.get(i).to_radians(),
+                    height: grid.height.get(k),
+                    ellipsoid: grid.ellipsoid,
+                };
+                let geocentric = GeocentricLocation::from(&geodetic);
+                let parallel = mag.parallel(&geocentric, geodetic.latitude);
+
+                for meridian in &meridians {
+                    let field = magnetic_field(self, mag.xyz(&parallel, meridian));
+                    fields.push(f(field));
+                }
+            }
+        }
+
+        fields
     }
 }
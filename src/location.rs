@@ -1,43 +1,252 @@
+use crate::ellipsoid::Ellipsoid;
+use crate::error::{Error, LocationField};
+#[cfg(feature = "geoid")]
+use crate::geoid::Geoid;
+#[cfg(feature = "libm")]
 use crate::num::Float;
 
-const A: f64 = 6378137.0;
-const F: f64 = 1.0 / 298.257223563;
-
 #[derive(Debug, Copy, Clone)]
 pub struct GeodeticLocation {
     pub longitude: f64,
     pub latitude: f64,
     pub height: f64,
+    pub ellipsoid: Ellipsoid,
 }
 
 impl GeodeticLocation {
-    pub fn new(longitude: f64, latitude: f64, height: f64) -> Self {
-        let lat = if (90.0 - latitude) < 1e-10 {
-            90.0 - 1e-6
-        } else if (90.0 + latitude) < 1e-10 {
-            -90.0 + 1e-6
-        } else {
-            latitude
-        };
+    /// Longitude and latitude in degrees, height above the WGS84 ellipsoid in metres.
+    pub fn new(longitude: f64, latitude: f64, height: f64) -> Result<Self, Error> {
+        GeodeticLocation::with_ellipsoid(longitude, latitude, height, Ellipsoid::WGS84)
+    }
 
-        GeodeticLocation {
+    /// Longitude and latitude in degrees, height above `ellipsoid` in metres.
+    pub fn with_ellipsoid(
+        longitude: f64,
+        latitude: f64,
+        height: f64,
+        ellipsoid: Ellipsoid,
+    ) -> Result<Self, Error> {
+        if !(-180.0..=360.0).contains(&longitude) {
+            return Err(Error::InvalidLocation {
+                field: LocationField::Longitude,
+                value: longitude,
+            });
+        }
+
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(Error::InvalidLocation {
+                field: LocationField::Latitude,
+                value: latitude,
+            });
+        }
+
+        if !height.is_finite() {
+            return Err(Error::InvalidLocation {
+                field: LocationField::Height,
+                value: height,
+            });
+        }
+
+        Ok(GeodeticLocation {
             longitude: longitude.to_radians(),
-            latitude: lat.to_radians(),
+            latitude: latitude.to_radians(),
+            height,
+            ellipsoid,
+        })
+    }
+
+    /// Longitude and latitude in degrees, height above mean sea level in metres, converted
+    /// to WGS84 through the embedded EGM96 geoid.
+    #[cfg(feature = "geoid")]
+    pub fn from_msl(longitude: f64, latitude: f64, height: f64) -> Result<Self, Error> {
+        GeodeticLocation::from_msl_with(longitude, latitude, height, &Geoid::egm96())
+    }
+
+    /// As [`GeodeticLocation::from_msl`], with the undulations of `geoid`.
+    #[cfg(feature = "geoid")]
+    pub fn from_msl_with(
+        longitude: f64,
+        latitude: f64,
+        height: f64,
+        geoid: &Geoid,
+    ) -> Result<Self, Error> {
+        let mut l = GeodeticLocation::new(longitude, latitude, height)?;
+        l.height += geoid.undulation(longitude, latitude);
+        Ok(l)
+    }
+
+    /// Iterates the latitude to convergence, which takes a handful of steps anywhere
+    /// outside the centre of the Earth.
+    pub fn from_ecef(l: &EcefLocation, ellipsoid: Ellipsoid) -> Self {
+        let a = ellipsoid.a();
+        let e_2 = ellipsoid.e_2();
+        let p = (l.x.powi(2) + l.y.powi(2)).sqrt();
+
+        let mut lat = l.z.atan2(p * (1.0 - e_2));
+        for _ in 0..10 {
+            let sin = lat.sin();
+            let rc = a / (1.0 - e_2 * sin.powi(2)).sqrt();
+            let next = (l.z + e_2 * rc * sin).atan2(p);
+            let done = next == lat;
+            lat = next;
+            if done {
+                break;
+            }
+        }
+
+        let sin = lat.sin();
+        let height = p * lat.cos() + l.z * sin - a * (1.0 - e_2 * sin.powi(2)).sqrt();
+
+        GeodeticLocation {
+            longitude: l.y.atan2(l.x),
+            latitude: lat,
             height,
+            ellipsoid,
         }
     }
 }
 
-pub(crate) struct GeocentricLocation {
+#[derive(Debug, Copy, Clone)]
+pub struct GeocentricLocation {
     pub longitude: f64,
     pub latitude: f64,
     pub radius: f64,
 }
 
+impl GeocentricLocation {
+    /// Longitude and geocentric latitude in degrees, radius from the centre in metres.
+    pub fn new(longitude: f64, latitude: f64, radius: f64) -> Result<Self, Error> {
+        if !(-180.0..=360.0).contains(&longitude) {
+            return Err(Error::InvalidLocation {
+                field: LocationField::Longitude,
+                value: longitude,
+            });
+        }
+
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(Error::InvalidLocation {
+                field: LocationField::Latitude,
+                value: latitude,
+            });
+        }
+
+        if !(radius.is_finite() && radius > 0.0) {
+            return Err(Error::InvalidLocation {
+                field: LocationField::Radius,
+                value: radius,
+            });
+        }
+
+        Ok(GeocentricLocation {
+            longitude: longitude.to_radians(),
+            latitude: latitude.to_radians(),
+            radius,
+        })
+    }
+
+    /// Colatitude in radians, in `0..=π`.
+    pub fn colatitude(&self) -> f64 {
+        core::f64::consts::FRAC_PI_2 - self.latitude
+    }
+}
+
+/// Earth-centred Earth-fixed position in metres.
+#[derive(Debug, Copy, Clone)]
+pub struct EcefLocation {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// Earth-centred inertial position in metres, sharing the z axis with [`EcefLocation`].
+#[derive(Debug, Copy, Clone)]
+pub struct EciLocation {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl EcefLocation {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        EcefLocation { x, y, z }
+    }
+
+    /// Rotates into the inertial frame at sidereal time `gmst` in radians.
+    pub fn to_eci(&self, gmst: f64) -> EciLocation {
+        let (x, y) = rotate_z(self.x, self.y, gmst);
+        EciLocation { x, y, z: self.z }
+    }
+}
+
+impl EciLocation {
+    pub fn new(x: f64, y: f64, z: f64) -> Self {
+        EciLocation { x, y, z }
+    }
+
+    /// Rotates into the Earth-fixed frame at sidereal time `gmst` in radians.
+    pub fn to_ecef(&self, gmst: f64) -> EcefLocation {
+        let (x, y) = rotate_z(self.x, self.y, -gmst);
+        EcefLocation { x, y, z: self.z }
+    }
+}
+
+#[inline]
+pub(crate) fn rotate_z(x: f64, y: f64, angle: f64) -> (f64, f64) {
+    let (sin, cos) = (angle.sin(), angle.cos());
+    (x * cos - y * sin, x * sin + y * cos)
+}
+
+impl From<&GeodeticLocation> for EcefLocation {
+    fn from(l: &GeodeticLocation) -> Self {
+        let e_2 = l.ellipsoid.e_2();
+        let rc = l.ellipsoid.a() / (1.0 - e_2 * l.latitude.sin().powi(2)).sqrt();
+
+        let p = (rc + l.height) * l.latitude.cos();
+        let z = (rc * (1.0 - e_2) + l.height) * l.latitude.sin();
+
+        EcefLocation {
+            x: p * l.longitude.cos(),
+            y: p * l.longitude.sin(),
+            z,
+        }
+    }
+}
+
+impl From<&GeocentricLocation> for EcefLocation {
+    fn from(l: &GeocentricLocation) -> Self {
+        let p = l.radius * l.latitude.cos();
+
+        EcefLocation {
+            x: p * l.longitude.cos(),
+            y: p * l.longitude.sin(),
+            z: l.radius * l.latitude.sin(),
+        }
+    }
+}
+
+impl From<&EcefLocation> for GeodeticLocation {
+    /// Converts to WGS84 coordinates.
+    fn from(l: &EcefLocation) -> Self {
+        GeodeticLocation::from_ecef(l, Ellipsoid::WGS84)
+    }
+}
+
+impl From<&EcefLocation> for GeocentricLocation {
+    fn from(l: &EcefLocation) -> Self {
+        let p = (l.x.powi(2) + l.y.powi(2)).sqrt();
+
+        GeocentricLocation {
+            longitude: l.y.atan2(l.x),
+            latitude: l.z.atan2(p),
+            radius: (p.powi(2) + l.z.powi(2)).sqrt(),
+        }
+    }
+}
+
 impl From<&GeodeticLocation> for GeocentricLocation {
     fn from(l: &GeodeticLocation) -> Self {
-        let e_2 = F * (2.0 - F);
-        let rc = A / (1.0 - e_2 * l.latitude.sin().powi(2)).sqrt();
+        let e_2 = l.ellipsoid.e_2();
+        let rc = l.ellipsoid.a() / (1.0 - e_2 * l.latitude.sin().powi(2)).sqrt();
 
         let p = (rc + l.height) * l.latitude.cos();
         let z = (rc * (1.0 - e_2) + l.height) * l.latitude.sin();
@@ -0,0 +1,155 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use crate::model::parse::{parse_field, ParseError, ParseErrorKind};
+
+/// Coefficients in the NOAA `.COF` layout used by the World Magnetic Model.
+///
+/// The first line holds the epoch, the model name and the release date, followed by one
+/// `n m g h g_sv h_sv` line per term and a line of `9`s as terminator.
+#[derive(Debug, Clone)]
+pub struct Cof {
+    pub epoch: f64,
+    pub name: String,
+    pub release_date: String,
+    deg: usize,
+    inner: Vec<[f64; 4]>,
+}
+
+impl Cof {
+    pub fn deg(&self) -> usize {
+        self.deg
+    }
+
+    pub(crate) fn inner(&self) -> &[[f64; 4]] {
+        &self.inner
+    }
+
+    #[cfg(not(feature = "libm"))]
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, ParseError> {
+        use std::io::{BufRead, BufReader};
+
+        let mut parser = Parser::default();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|e| ParseError::new(i + 1, ParseErrorKind::Io(e.kind())))?;
+            if parser.push(i + 1, &line)? {
+                break;
+            }
+        }
+
+        parser.finish()
+    }
+}
+
+impl FromStr for Cof {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::default();
+        for (i, line) in s.lines().enumerate() {
+            if parser.push(i + 1, line)? {
+                break;
+            }
+        }
+
+        parser.finish()
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    line: usize,
+    header: Option<(f64, String, String)>,
+    inner: Vec<[f64; 4]>,
+    n: usize,
+    m: usize,
+    done: bool,
+}
+
+impl Parser {
+    fn push(&mut self, line: usize, content: &str) -> Result<bool, ParseError> {
+        self.line = line;
+
+        let content = content.trim();
+        if content.is_empty() {
+            return Ok(false);
+        }
+
+        if self.header.is_none() {
+            let mut fields = content.split_whitespace();
+            let epoch = parse_field(&mut fields, line)
+                .map_err(|_| ParseError::new(line, ParseErrorKind::InvalidHeader))?;
+            let name = fields.next().unwrap_or_default().to_string();
+            let release_date = fields.next().unwrap_or_default().to_string();
+
+            self.header = Some((epoch, name, release_date));
+            return Ok(false);
+        }
+
+        if content.starts_with("9999") {
+            self.done = true;
+            return Ok(true);
+        }
+
+        let mut fields = content.split_whitespace();
+        let n: usize = parse_field(&mut fields, line)?;
+        let m: usize = parse_field(&mut fields, line)?;
+
+        let (n_next, m_next) = if self.m < self.n {
+            (self.n, self.m + 1)
+        } else {
+            (self.n + 1, 0)
+        };
+
+        if n != n_next || m != m_next {
+            return Err(ParseError::new(
+                line,
+                ParseErrorKind::UnexpectedTerm { n, m },
+            ));
+        }
+
+        let g = parse_field(&mut fields, line)?;
+        let h = parse_field(&mut fields, line)?;
+        let g_sv = parse_field(&mut fields, line)?;
+        let h_sv = parse_field(&mut fields, line)?;
+
+        self.inner.push([g, h, g_sv, h_sv]);
+        self.n = n;
+        self.m = m;
+
+        Ok(false)
+    }
+
+    fn finish(self) -> Result<Cof, ParseError> {
+        let Some((epoch, name, release_date)) = self.header else {
+            return Err(ParseError::new(self.line, ParseErrorKind::Empty));
+        };
+
+        if !self.done {
+            return Err(ParseError::new(
+                self.line,
+                ParseErrorKind::MissingTerminator,
+            ));
+        }
+
+        if self.inner.is_empty() {
+            return Err(ParseError::new(self.line, ParseErrorKind::Empty));
+        }
+
+        if self.m != self.n {
+            return Err(ParseError::new(
+                self.line,
+                ParseErrorKind::IncompleteDegree(self.n),
+            ));
+        }
+
+        Ok(Cof {
+            epoch,
+            name,
+            release_date,
+            deg: self.n,
+            inner: self.inner,
+        })
+    }
+}
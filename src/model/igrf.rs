@@ -1,13 +1,22 @@
-use crate::model::Model;
-use crate::num::{Float, NumFrom};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::Error;
+use crate::model::{Coefficients, Model, Sealed};
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::num::NumFrom;
 
 const IGRF_EPOCH_INTERVAL: f64 = 5.0;
 const IGRF_START: f64 = 1900.0;
-const IGRF_END: f64 = 2025.0;
 const IGRF_N_1900: usize = 10;
 const IGRF_N_2000: usize = 13;
-const IGRF_COF_G: [[f64; 26]; 104] = include!(concat!(env!("OUT_DIR"), "/IGRF_COF_G"));
-const IGRF_COF_H: [[f64; 26]; 104] = include!(concat!(env!("OUT_DIR"), "/IGRF_COF_H"));
+const IGRF_COF_G: &[&[f64]] = include!(concat!(env!("OUT_DIR"), "/IGRF_COF_G"));
+const IGRF_COF_H: &[&[f64]] = include!(concat!(env!("OUT_DIR"), "/IGRF_COF_H"));
+
+/// Each row holds one coefficient per epoch, followed by the predictive secular variation.
+const IGRF_EPOCHS: usize = IGRF_COF_G[0].len() - 1;
+const IGRF_END: f64 = IGRF_START + IGRF_EPOCH_INTERVAL * IGRF_EPOCHS as f64;
 
 #[inline]
 fn nm_to_index(n: usize, m: usize) -> usize {
@@ -17,7 +26,7 @@ fn nm_to_index(n: usize, m: usize) -> usize {
 #[inline]
 fn year_to_index(t: f64) -> usize {
     let v = ((t - IGRF_START) / IGRF_EPOCH_INTERVAL).floor();
-    unsafe { usize::from_unchecked(v) }
+    unsafe { usize::from_unchecked(v) }.min(IGRF_EPOCHS - 1)
 }
 
 #[inline]
@@ -30,14 +39,10 @@ pub struct IGRF {
     deg: usize,
     t0: f64,
     t: f64,
-    inner: [[f64; 4]; 104],
+    inner: Coefficients,
 }
 
 impl Model for IGRF {
-    fn is_valid(t: f64) -> bool {
-        IGRF_START <= t && t <= IGRF_END
-    }
-
     fn deg(&self) -> usize {
         self.deg
     }
@@ -51,30 +56,40 @@ impl Model for IGRF {
     }
 
     fn g(&self, n: usize, m: usize) -> f64 {
-        let i = nm_to_index(n, m);
-        self.inner[i][0]
+        self.inner.g(n, m)
     }
 
     fn h(&self, n: usize, m: usize) -> f64 {
-        let i = nm_to_index(n, m);
-        self.inner[i][1]
+        self.inner.h(n, m)
     }
 
     fn g_sv(&self, n: usize, m: usize) -> f64 {
-        let i = nm_to_index(n, m);
-        self.inner[i][2]
+        self.inner.g_sv(n, m)
     }
 
     fn h_sv(&self, n: usize, m: usize) -> f64 {
-        let i = nm_to_index(n, m);
-        self.inner[i][3]
+        self.inner.h_sv(n, m)
+    }
+
+    fn adjusted(&self, _: Sealed) -> Option<&[[f64; 4]]> {
+        Some(self.inner.as_slice())
     }
 }
 
 impl IGRF {
-    pub fn new(decimal: f64) -> Option<Self> {
+    #[inline]
+    fn is_valid(t: f64) -> bool {
+        IGRF_START <= t && t <= IGRF_END
+    }
+
+    pub fn new(decimal: f64) -> Result<Self, Error> {
         if !IGRF::is_valid(decimal) {
-            return None;
+            return Err(Error::OutOfRange {
+                model: "IGRF",
+                decimal,
+                start: IGRF_START,
+                end: IGRF_END,
+            });
         }
 
         let n = if decimal < 2000.0 {
@@ -85,9 +100,9 @@ impl IGRF {
 
         let iy = year_to_index(decimal);
         let t0 = index_to_year(iy);
-        let inner = IGRF::build(t0, iy, n);
+        let inner = Coefficients::new(&IGRF::build(iy, n), decimal - t0);
 
-        Some(IGRF {
+        Ok(IGRF {
             deg: n,
             t0,
             t: decimal,
@@ -95,12 +110,27 @@ impl IGRF {
         })
     }
 
+    /// The same model at `decimal`, rebuilding the coefficients only when an epoch
+    /// boundary is crossed.
+    pub fn at_time(&self, decimal: f64) -> Result<Self, Error> {
+        if !IGRF::is_valid(decimal) || year_to_index(decimal) != year_to_index(self.t) {
+            return IGRF::new(decimal);
+        }
+
+        Ok(IGRF {
+            deg: self.deg,
+            t0: self.t0,
+            t: decimal,
+            inner: self.inner.retime(decimal - self.t0),
+        })
+    }
+
     #[inline]
-    fn build(t0: f64, iy: usize, n: usize) -> [[f64; 4]; 104] {
-        let mut inner = [[0.0; 4]; 104];
+    fn build(iy: usize, n: usize) -> Vec<[f64; 4]> {
+        let mut inner = vec![[0.0; 4]; nm_to_index(n, n) + 1];
 
-        if t0 == (IGRF_END - IGRF_EPOCH_INTERVAL) {
-            let il = IGRF_COF_G[0].len() - 1;
+        if iy == IGRF_EPOCHS - 1 {
+            let il = IGRF_EPOCHS;
 
             for j in 1..=n {
                 for i in 0..=j {
@@ -124,8 +154,8 @@ impl IGRF {
                     let gl = IGRF_COF_G[ii][iy + 1];
                     let hl = IGRF_COF_H[ii][iy + 1];
 
-                    let g_sv = (gl - g) / 5.0;
-                    let h_sv = (hl - h) / 5.0;
+                    let g_sv = (gl - g) / IGRF_EPOCH_INTERVAL;
+                    let h_sv = (hl - h) / IGRF_EPOCH_INTERVAL;
 
                     inner[ii] = [g, h, g_sv, h_sv];
                 }
@@ -1,22 +1,128 @@
+use alloc::vec::Vec;
+
+use crate::field::Uncertainty;
+
+#[cfg(feature = "wmm")]
+pub use cof::Cof;
 #[cfg(feature = "igrf")]
 pub use igrf::IGRF;
+pub(crate) use parse::parse_field;
+pub use parse::{ParseError, ParseErrorKind};
+pub use shc::{Shc, ShcModel};
 #[cfg(feature = "wmm")]
-pub use wmm::WMM;
+pub use wmm::{WmmRelease, WMM};
 
+#[cfg(feature = "wmm")]
+mod cof;
 #[cfg(feature = "igrf")]
 mod igrf;
+mod parse;
+mod shc;
 #[cfg(feature = "wmm")]
 mod wmm;
 
-pub(crate) trait Model {
-    fn is_valid(t: f64) -> bool;
+/// A spherical-harmonic model of the main field.
+///
+/// Coefficients are Schmidt semi-normalized Gauss coefficients in nT, referred to the
+/// radius of 6371.2 km, and secular variation in nT/year. They are queried for every
+/// `1 <= n <= deg()` and `0 <= m <= n`, and combined linearly as
+/// `g(n, m) + (t() - t0()) * g_sv(n, m)`.
+///
+/// Implementing this trait is enough to evaluate the model through [`Geomag`](crate::Geomag).
+pub trait Model {
+    /// Maximum degree of the expansion.
     fn deg(&self) -> usize;
+    /// Epoch of the coefficients, in decimal years.
     fn t0(&self) -> f64;
+    /// Time the model is evaluated at, in decimal years.
     fn t(&self) -> f64;
+    /// Gauss coefficient `g` at the epoch.
     fn g(&self, n: usize, m: usize) -> f64;
+    /// Gauss coefficient `h` at the epoch, zero for `m = 0`.
     fn h(&self, n: usize, m: usize) -> f64;
+    /// Secular variation of `g`.
     fn g_sv(&self, n: usize, m: usize) -> f64;
+    /// Secular variation of `h`.
     fn h_sv(&self, n: usize, m: usize) -> f64;
+
+    /// Uncertainty of results with horizontal intensity `h` in nT, for models that
+    /// publish an error model.
+    fn uncertainty(&self, _h: f64) -> Option<Uncertainty> {
+        None
+    }
+
+    /// Coefficients already adjusted to `t()`, for the models of this crate. The token
+    /// cannot be named outside the crate, so the method can neither be called nor
+    /// overridden there.
+    #[doc(hidden)]
+    fn adjusted(&self, _: Sealed) -> Option<&[[f64; 4]]> {
+        None
+    }
+}
+
+/// Token restricting [`Model::adjusted`] to this crate.
+#[derive(Copy, Clone)]
+pub struct Sealed(());
+
+impl Sealed {
+    pub(crate) const TOKEN: Sealed = Sealed(());
+}
+
+#[inline]
+fn nm_to_index(n: usize, m: usize) -> usize {
+    n * (n + 1) / 2 + m - 1
+}
+
+/// Coefficients at the epoch, and adjusted once to the evaluation time, sized to the
+/// degree of the model.
+pub(crate) struct Coefficients {
+    epoch: Vec<[f64; 4]>,
+    inner: Vec<[f64; 4]>,
+}
+
+impl Coefficients {
+    /// Adjusts `[g, h, g_sv, h_sv]` at the epoch by `dt` years.
+    pub(crate) fn new(epoch: &[[f64; 4]], dt: f64) -> Self {
+        let epoch = epoch.to_vec();
+        let inner = Coefficients::adjust(&epoch, dt);
+
+        Coefficients { epoch, inner }
+    }
+
+    /// The same coefficients adjusted by `dt` years from the epoch instead.
+    pub(crate) fn retime(&self, dt: f64) -> Self {
+        Coefficients {
+            epoch: self.epoch.clone(),
+            inner: Coefficients::adjust(&self.epoch, dt),
+        }
+    }
+
+    fn adjust(epoch: &[[f64; 4]], dt: f64) -> Vec<[f64; 4]> {
+        epoch
+            .iter()
+            .map(|&[g, h, g_sv, h_sv]| [g + dt * g_sv, h + dt * h_sv, g_sv, h_sv])
+            .collect()
+    }
+
+    pub(crate) fn g(&self, n: usize, m: usize) -> f64 {
+        self.epoch[nm_to_index(n, m)][0]
+    }
+
+    pub(crate) fn h(&self, n: usize, m: usize) -> f64 {
+        self.epoch[nm_to_index(n, m)][1]
+    }
+
+    pub(crate) fn g_sv(&self, n: usize, m: usize) -> f64 {
+        self.inner[nm_to_index(n, m)][2]
+    }
+
+    pub(crate) fn h_sv(&self, n: usize, m: usize) -> f64 {
+        self.inner[nm_to_index(n, m)][3]
+    }
+
+    pub(crate) fn as_slice(&self) -> &[[f64; 4]] {
+        &self.inner
+    }
 }
 
 pub(crate) trait Gauss {
@@ -26,7 +132,7 @@ pub(crate) trait Gauss {
     fn dh(&self, n: usize, m: usize) -> f64;
 }
 
-impl<T: Model> Gauss for T {
+impl<T: Model + ?Sized> Gauss for T {
     #[inline]
     fn g(&self, n: usize, m: usize) -> f64 {
         self.g(n, m) + (self.t() - self.t0()) * self.g_sv(n, m)
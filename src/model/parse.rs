@@ -0,0 +1,84 @@
+use core::fmt;
+use core::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    Empty,
+    InvalidHeader,
+    InvalidNumber,
+    MissingField,
+    UnexpectedTerm {
+        n: usize,
+        m: usize,
+    },
+    IncompleteDegree(usize),
+    /// The times of an SHC file are not strictly increasing.
+    UnorderedTimes,
+    MissingTerminator,
+    #[cfg(not(feature = "libm"))]
+    Io(std::io::ErrorKind),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub(crate) fn new(line: usize, kind: ParseErrorKind) -> Self {
+        ParseError { line, kind }
+    }
+
+    /// The 1-based line number the error was found on.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Empty => write!(f, "no coefficients found"),
+            ParseErrorKind::InvalidHeader => write!(f, "invalid header"),
+            ParseErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ParseErrorKind::MissingField => write!(f, "missing field"),
+            ParseErrorKind::UnexpectedTerm { n, m } => {
+                write!(f, "unexpected term n = {}, m = {}", n, m)
+            }
+            ParseErrorKind::IncompleteDegree(n) => write!(f, "degree {} is incomplete", n),
+            ParseErrorKind::UnorderedTimes => write!(f, "times are not strictly increasing"),
+            ParseErrorKind::MissingTerminator => write!(f, "missing terminator"),
+            #[cfg(not(feature = "libm"))]
+            ParseErrorKind::Io(kind) => write!(f, "io error: {:?}", kind),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+#[cfg(not(feature = "libm"))]
+impl std::error::Error for ParseError {}
+
+#[inline]
+pub(crate) fn parse_field<'a, T, I>(fields: &mut I, line: usize) -> Result<T, ParseError>
+where
+    T: FromStr,
+    I: Iterator<Item = &'a str>,
+{
+    let value = fields
+        .next()
+        .ok_or(ParseError::new(line, ParseErrorKind::MissingField))?;
+
+    value
+        .parse()
+        .map_err(|_| ParseError::new(line, ParseErrorKind::InvalidNumber))
+}
@@ -0,0 +1,300 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+use crate::error::Error;
+use crate::model::parse::{parse_field, ParseError, ParseErrorKind};
+use crate::model::{Coefficients, Model, Sealed};
+use crate::spline::{augment_breaks, basis, find_span, fit};
+
+#[inline]
+fn nm_to_index(n: usize, m: usize) -> usize {
+    n * (n + 1) / 2 + m - 1
+}
+
+/// Coefficients in the SHC layout used by IGRF, CHAOS and other field model releases.
+///
+/// Lines starting with `#` are comments. The first remaining line holds the minimum and
+/// maximum degree, the number of times, the spline order and the step between spline
+/// breaks, followed by a row of times and one `n m values...` row per term, where a
+/// negative `m` denotes `h`.
+#[derive(Debug, Clone)]
+pub struct Shc {
+    nmin: usize,
+    nmax: usize,
+    order: usize,
+    times: Vec<f64>,
+    knots: Vec<f64>,
+    inner: Vec<Vec<f64>>,
+}
+
+impl Shc {
+    pub fn nmin(&self) -> usize {
+        self.nmin
+    }
+
+    pub fn nmax(&self) -> usize {
+        self.nmax
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    pub fn times(&self) -> &[f64] {
+        &self.times
+    }
+
+    #[cfg(not(feature = "libm"))]
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self, ParseError> {
+        use std::io::{BufRead, BufReader};
+
+        let mut parser = Parser::default();
+        for (i, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line.map_err(|e| ParseError::new(i + 1, ParseErrorKind::Io(e.kind())))?;
+            parser.push(i + 1, &line)?;
+        }
+
+        parser.finish()
+    }
+}
+
+impl FromStr for Shc {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser::default();
+        for (i, line) in s.lines().enumerate() {
+            parser.push(i + 1, line)?;
+        }
+
+        parser.finish()
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    line: usize,
+    params: Option<(usize, usize, usize, usize, usize)>,
+    times: Vec<f64>,
+    inner: Vec<Vec<f64>>,
+    filled: Vec<[bool; 2]>,
+}
+
+impl Parser {
+    fn push(&mut self, line: usize, content: &str) -> Result<(), ParseError> {
+        self.line = line;
+
+        let content = content.trim();
+        if content.is_empty() || content.starts_with('#') {
+            return Ok(());
+        }
+
+        let mut fields = content.split_whitespace();
+
+        let Some((nmin, nmax, ntimes, _, _)) = self.params else {
+            let header = |_| ParseError::new(line, ParseErrorKind::InvalidHeader);
+            let nmin: usize = parse_field(&mut fields, line).map_err(header)?;
+            let nmax: usize = parse_field(&mut fields, line).map_err(header)?;
+            let ntimes: usize = parse_field(&mut fields, line).map_err(header)?;
+            let order: usize = parse_field(&mut fields, line).map_err(header)?;
+            let step: usize = parse_field(&mut fields, line).map_err(header)?;
+
+            if nmin == 0 || nmin > nmax || ntimes == 0 || order == 0 || step == 0 {
+                return Err(ParseError::new(line, ParseErrorKind::InvalidHeader));
+            }
+
+            let size = nm_to_index(nmax, nmax) + 1;
+            self.params = Some((nmin, nmax, ntimes, order, step));
+            self.inner = vec![vec![0.0; 2 * size]; ntimes];
+            self.filled = vec![[false; 2]; size];
+            return Ok(());
+        };
+
+        if self.times.is_empty() {
+            for _ in 0..ntimes {
+                let t: f64 = parse_field(&mut fields, line)?;
+                if self.times.last().is_some_and(|&last| t <= last) {
+                    return Err(ParseError::new(line, ParseErrorKind::UnorderedTimes));
+                }
+                self.times.push(t);
+            }
+
+            return Ok(());
+        }
+
+        let n: usize = parse_field(&mut fields, line)?;
+        let m: isize = parse_field(&mut fields, line)?;
+        let k = m.unsigned_abs();
+
+        if n < nmin || n > nmax || k > n || (m < 0 && k == 0) {
+            return Err(ParseError::new(
+                line,
+                ParseErrorKind::UnexpectedTerm { n, m: k },
+            ));
+        }
+
+        let i = nm_to_index(n, k);
+        let j = usize::from(m < 0);
+        if self.filled[i][j] {
+            return Err(ParseError::new(
+                line,
+                ParseErrorKind::UnexpectedTerm { n, m: k },
+            ));
+        }
+
+        for values in self.inner.iter_mut() {
+            values[2 * i + j] = parse_field(&mut fields, line)?;
+        }
+        self.filled[i][j] = true;
+
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Shc, ParseError> {
+        let Some((nmin, nmax, _, order, step)) = self.params else {
+            return Err(ParseError::new(self.line, ParseErrorKind::Empty));
+        };
+
+        if self.times.is_empty() {
+            return Err(ParseError::new(self.line, ParseErrorKind::Empty));
+        }
+
+        for n in nmin..=nmax {
+            let complete = (0..=n).all(|m| {
+                let [g, h] = self.filled[nm_to_index(n, m)];
+                g && (h || m == 0)
+            });
+
+            if !complete {
+                return Err(ParseError::new(
+                    self.line,
+                    ParseErrorKind::IncompleteDegree(n),
+                ));
+            }
+        }
+
+        if self.times.len() == 1 {
+            return Ok(Shc {
+                nmin,
+                nmax,
+                order,
+                times: self.times,
+                knots: Vec::new(),
+                inner: self.inner,
+            });
+        }
+
+        let last = self.times.len() - 1;
+        let mut breaks: Vec<f64> = self.times.iter().step_by(step).copied().collect();
+        if !last.is_multiple_of(step) {
+            breaks.push(self.times[last]);
+        }
+
+        if breaks.len() + order - 2 > self.times.len() {
+            return Err(ParseError::new(self.line, ParseErrorKind::InvalidHeader));
+        }
+
+        let knots = augment_breaks(&breaks, order);
+        let inner = fit(&knots, order, &self.times, &self.inner);
+
+        Ok(Shc {
+            nmin,
+            nmax,
+            order,
+            times: self.times,
+            knots,
+            inner,
+        })
+    }
+}
+
+/// A time-dependent model built from [`Shc`] coefficients.
+///
+/// Files with a spline order of 2 are interpolated linearly between times, as for IGRF;
+/// higher orders are fitted with B-splines over every `step`-th time, as for CHAOS.
+pub struct ShcModel {
+    deg: usize,
+    t: f64,
+    inner: Coefficients,
+}
+
+impl Model for ShcModel {
+    fn deg(&self) -> usize {
+        self.deg
+    }
+
+    fn t0(&self) -> f64 {
+        self.t
+    }
+
+    fn t(&self) -> f64 {
+        self.t
+    }
+
+    fn g(&self, n: usize, m: usize) -> f64 {
+        self.inner.g(n, m)
+    }
+
+    fn h(&self, n: usize, m: usize) -> f64 {
+        self.inner.h(n, m)
+    }
+
+    fn g_sv(&self, n: usize, m: usize) -> f64 {
+        self.inner.g_sv(n, m)
+    }
+
+    fn h_sv(&self, n: usize, m: usize) -> f64 {
+        self.inner.h_sv(n, m)
+    }
+
+    fn adjusted(&self, _: Sealed) -> Option<&[[f64; 4]]> {
+        Some(self.inner.as_slice())
+    }
+}
+
+impl ShcModel {
+    pub fn from_shc(shc: &Shc, decimal: f64) -> Result<Self, Error> {
+        let start = shc.times[0];
+        let end = shc.times[shc.times.len() - 1];
+
+        if shc.times.len() > 1 && !(start..=end).contains(&decimal) {
+            return Err(Error::OutOfRange {
+                model: "SHC",
+                decimal,
+                start,
+                end,
+            });
+        }
+
+        let size = shc.inner[0].len() / 2;
+        let mut inner = vec![[0.0; 4]; size];
+
+        if shc.times.len() == 1 {
+            for (i, v) in inner.iter_mut().enumerate() {
+                v[0] = shc.inner[0][2 * i];
+                v[1] = shc.inner[0][2 * i + 1];
+            }
+        } else {
+            let span = find_span(&shc.knots, shc.order, decimal);
+            let (n, dn) = basis(&shc.knots, shc.order, span, decimal);
+            let first = span + 1 - shc.order;
+
+            for (i, v) in inner.iter_mut().enumerate() {
+                for (r, (nr, dnr)) in n.iter().zip(&dn).enumerate() {
+                    let c = &shc.inner[first + r];
+                    v[0] += nr * c[2 * i];
+                    v[1] += nr * c[2 * i + 1];
+                    v[2] += dnr * c[2 * i];
+                    v[3] += dnr * c[2 * i + 1];
+                }
+            }
+        }
+
+        Ok(ShcModel {
+            deg: shc.nmax,
+            t: decimal,
+            inner: Coefficients::new(&inner, 0.0),
+        })
+    }
+}
@@ -1,28 +1,110 @@
-use crate::model::Model;
+use core::str::FromStr;
 
-const WMM_START: f64 = 2020.0;
-const WMM_END: f64 = 2025.0;
-const WMM_T0: f64 = WMM_START;
+use crate::error::Error;
+use crate::field::Uncertainty;
+use crate::model::{Coefficients, Cof, Model, Sealed};
+#[cfg(feature = "libm")]
+use crate::num::Float;
+
+const WMM_INTERVAL: f64 = 5.0;
 const WMM_N: usize = 12;
-const WMM_COF: [[f64; 4]; 90] = include!(concat!(env!("OUT_DIR"), "/WMM_COF"));
+static WMM2020_COF: [[f64; 4]; 90] = include!(concat!(env!("OUT_DIR"), "/WMM2020_COF"));
+static WMM2025_COF: [[f64; 4]; 90] = include!(concat!(env!("OUT_DIR"), "/WMM2025_COF"));
+
+/// Releases of the World Magnetic Model compiled into the crate, each valid for five
+/// years from its epoch.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WmmRelease {
+    Wmm2020,
+    Wmm2025,
+}
+
+impl WmmRelease {
+    pub const ALL: [WmmRelease; 2] = [WmmRelease::Wmm2020, WmmRelease::Wmm2025];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WmmRelease::Wmm2020 => "WMM2020",
+            WmmRelease::Wmm2025 => "WMM2025",
+        }
+    }
+
+    pub fn epoch(self) -> f64 {
+        match self {
+            WmmRelease::Wmm2020 => 2020.0,
+            WmmRelease::Wmm2025 => 2025.0,
+        }
+    }
+
+    pub fn end(self) -> f64 {
+        self.epoch() + WMM_INTERVAL
+    }
+
+    pub fn is_valid(self, t: f64) -> bool {
+        (self.epoch()..=self.end()).contains(&t)
+    }
+
+    /// The latest release whose validity window contains `t`.
+    pub fn containing(t: f64) -> Option<Self> {
+        WmmRelease::ALL.into_iter().rev().find(|r| r.is_valid(t))
+    }
+
+    /// Global error model of the release's technical report, for results with horizontal
+    /// intensity `h` in nT.
+    pub fn uncertainty(self, h: f64) -> Uncertainty {
+        let ([x, y, z, h_err, f], i, d_h) = match self {
+            WmmRelease::Wmm2020 => ([131.0, 94.0, 157.0, 128.0, 148.0], 0.21_f64, 5625.0),
+            WmmRelease::Wmm2025 => ([137.0, 89.0, 141.0, 133.0, 138.0], 0.20, 5417.0),
+        };
+        let d = (0.26_f64.powi(2) + (d_h / h).powi(2)).sqrt();
+
+        Uncertainty {
+            x,
+            y,
+            z,
+            h: h_err,
+            f,
+            d: d.to_radians(),
+            i: i.to_radians(),
+        }
+    }
+
+    fn cof(self) -> &'static [[f64; 4]] {
+        match self {
+            WmmRelease::Wmm2020 => &WMM2020_COF,
+            WmmRelease::Wmm2025 => &WMM2025_COF,
+        }
+    }
+}
+
+impl FromStr for WmmRelease {
+    type Err = Error;
+
+    /// Parses release names such as `WMM2025` or `WMM-2025`, case-insensitively.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let matches = |name: &str| {
+            s.chars()
+                .filter(|c| *c != '-')
+                .map(|c| c.to_ascii_uppercase())
+                .eq(name.chars())
+        };
 
-#[inline]
-fn nm_to_index(n: usize, m: usize) -> usize {
-    n * (n + 1) / 2 + m - 1
+        WmmRelease::ALL
+            .into_iter()
+            .find(|r| matches(r.name()))
+            .ok_or(Error::UnknownRelease { model: "WMM" })
+    }
 }
 
 pub struct WMM {
+    release: Option<WmmRelease>,
     deg: usize,
     t0: f64,
     t: f64,
-    inner: [[f64; 4]; 90],
+    inner: Coefficients,
 }
 
 impl Model for WMM {
-    fn is_valid(t: f64) -> bool {
-        WMM_START <= t && t <= WMM_END
-    }
-
     fn deg(&self) -> usize {
         self.deg
     }
@@ -36,37 +118,112 @@ impl Model for WMM {
     }
 
     fn g(&self, n: usize, m: usize) -> f64 {
-        let i = nm_to_index(n, m);
-        self.inner[i][0]
+        self.inner.g(n, m)
     }
 
     fn h(&self, n: usize, m: usize) -> f64 {
-        let i = nm_to_index(n, m);
-        self.inner[i][1]
+        self.inner.h(n, m)
     }
 
     fn g_sv(&self, n: usize, m: usize) -> f64 {
-        let i = nm_to_index(n, m);
-        self.inner[i][2]
+        self.inner.g_sv(n, m)
     }
 
     fn h_sv(&self, n: usize, m: usize) -> f64 {
-        let i = nm_to_index(n, m);
-        self.inner[i][3]
+        self.inner.h_sv(n, m)
+    }
+
+    fn uncertainty(&self, h: f64) -> Option<Uncertainty> {
+        self.release.map(|r| r.uncertainty(h))
+    }
+
+    fn adjusted(&self, _: Sealed) -> Option<&[[f64; 4]]> {
+        Some(self.inner.as_slice())
     }
 }
 
 impl WMM {
-    pub fn new(decimal: f64) -> Option<Self> {
-        if !WMM::is_valid(decimal) {
-            return None;
+    /// Uses the latest release valid at `decimal`.
+    pub fn new(decimal: f64) -> Result<Self, Error> {
+        let release = WmmRelease::containing(decimal).ok_or(Error::OutOfRange {
+            model: "WMM",
+            decimal,
+            start: WmmRelease::ALL[0].epoch(),
+            end: WmmRelease::ALL[WmmRelease::ALL.len() - 1].end(),
+        })?;
+
+        WMM::release(release, decimal)
+    }
+
+    pub fn release(release: WmmRelease, decimal: f64) -> Result<Self, Error> {
+        if !release.is_valid(decimal) {
+            return Err(Error::OutOfRange {
+                model: release.name(),
+                decimal,
+                start: release.epoch(),
+                end: release.end(),
+            });
         }
 
-        Some(WMM {
+        Ok(WMM {
+            release: Some(release),
             deg: WMM_N,
-            t0: WMM_T0,
+            t0: release.epoch(),
+            t: decimal,
+            inner: Coefficients::new(release.cof(), decimal - release.epoch()),
+        })
+    }
+
+    /// The same coefficients at `decimal`, which must lie within their five-year window.
+    pub fn at_time(&self, decimal: f64) -> Result<Self, Error> {
+        let end = self.t0 + WMM_INTERVAL;
+        if !(self.t0..=end).contains(&decimal) {
+            return Err(Error::OutOfRange {
+                model: "WMM",
+                decimal,
+                start: self.t0,
+                end,
+            });
+        }
+
+        Ok(WMM {
+            release: self.release,
+            deg: self.deg,
+            t0: self.t0,
             t: decimal,
-            inner: WMM_COF,
+            inner: self.inner.retime(decimal - self.t0),
         })
     }
+
+    /// Builds a model from coefficients loaded at runtime, valid for five years from
+    /// the epoch in the file header.
+    pub fn from_cof(cof: &Cof, decimal: f64) -> Result<Self, Error> {
+        let end = cof.epoch + WMM_INTERVAL;
+        if !(cof.epoch..=end).contains(&decimal) {
+            return Err(Error::OutOfRange {
+                model: "WMM",
+                decimal,
+                start: cof.epoch,
+                end,
+            });
+        }
+
+        Ok(WMM {
+            release: None,
+            deg: cof.deg(),
+            t0: cof.epoch,
+            t: decimal,
+            inner: Coefficients::new(cof.inner(), decimal - cof.epoch),
+        })
+    }
+
+    /// Parses the content of a `.COF` file, as for [`Cof`], and builds the model from it.
+    pub fn from_cof_str(s: &str, decimal: f64) -> Result<Self, Error> {
+        WMM::from_cof(&s.parse()?, decimal)
+    }
+
+    #[cfg(not(feature = "libm"))]
+    pub fn from_reader<R: std::io::Read>(reader: R, decimal: f64) -> Result<Self, Error> {
+        WMM::from_cof(&Cof::from_reader(reader)?, decimal)
+    }
 }
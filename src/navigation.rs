@@ -0,0 +1,205 @@
+//! Quantities for navigating with a compass, derived from field results.
+
+use core::f64::consts::{PI, TAU};
+
+use crate::field::MagneticField;
+use crate::location::GeodeticLocation;
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::num::NumFrom;
+
+/// Latitude in degrees beyond which polar navigation uses grid variation.
+pub const POLAR_LATITUDE: f64 = 55.0;
+
+/// An angle in radians, with its annual change in radians per year.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Angle {
+    pub value: f64,
+    pub rate: f64,
+}
+
+/// Wraps an angle in radians into `-π..=π`.
+#[inline]
+pub(crate) fn wrap(a: f64) -> f64 {
+    let r = (a + PI) % TAU;
+    let r = if r <= 0.0 { r + TAU } else { r };
+    r - PI
+}
+
+/// Grid variation, the bearing of magnetic north from grid north, as defined by the WMM
+/// software.
+///
+/// North of 55° N it is the declination minus the longitude, south of 55° S the
+/// declination plus the longitude, as on a polar-stereographic grid. In between it is the
+/// declination minus the convergence of the UTM zone containing `location`.
+pub fn grid_variation(field: &MagneticField, location: &GeodeticLocation) -> Angle {
+    let latitude = location.latitude.to_degrees();
+
+    let value = if latitude >= POLAR_LATITUDE {
+        field.d - location.longitude
+    } else if latitude <= -POLAR_LATITUDE {
+        field.d + location.longitude
+    } else {
+        field.d - TransverseMercator::utm(location).convergence(location)
+    };
+
+    Angle {
+        value: wrap(value),
+        rate: field.d_dot,
+    }
+}
+
+/// Reference directions that bearings are measured clockwise from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum North {
+    True,
+    Magnetic,
+    Grid,
+}
+
+/// Angles between true, magnetic and grid north at a point.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Correction {
+    /// Bearing of magnetic north from true north.
+    pub declination: Angle,
+    /// Bearing of grid north from true north, which does not change with time.
+    pub convergence: f64,
+}
+
+impl Correction {
+    /// Declination of a field result and convergence of the grid in radians, as given by
+    /// [`TransverseMercator::convergence`].
+    pub fn new(field: &MagneticField, convergence: f64) -> Self {
+        Correction {
+            declination: Angle {
+                value: field.d,
+                rate: field.d_dot,
+            },
+            convergence,
+        }
+    }
+
+    /// Bearing of magnetic north from grid north: the declination minus the convergence.
+    pub fn total(&self) -> Angle {
+        Angle {
+            value: wrap(self.declination.value - self.convergence),
+            rate: self.declination.rate,
+        }
+    }
+
+    /// Converts a bearing in radians measured from `from` into one measured from `to`, in
+    /// `0..2π`, with its annual change.
+    pub fn convert(&self, bearing: f64, from: North, to: North) -> Angle {
+        let from = self.offset(from);
+        let to = self.offset(to);
+
+        let value = wrap(bearing + from.value - to.value);
+        Angle {
+            value: if value < 0.0 { value + TAU } else { value },
+            rate: from.rate - to.rate,
+        }
+    }
+
+    fn offset(&self, north: North) -> Angle {
+        match north {
+            North::True => Angle {
+                value: 0.0,
+                rate: 0.0,
+            },
+            North::Magnetic => self.declination,
+            North::Grid => Angle {
+                value: self.convergence,
+                rate: 0.0,
+            },
+        }
+    }
+}
+
+/// UTM zone number of a location, including the exceptions around Norway and Svalbard.
+pub fn utm_zone(location: &GeodeticLocation) -> u32 {
+    let latitude = location.latitude.to_degrees();
+    let longitude = wrap(location.longitude).to_degrees();
+    let longitude = if longitude >= 180.0 {
+        longitude - 360.0
+    } else {
+        longitude
+    };
+
+    if (56.0..64.0).contains(&latitude) && (3.0..12.0).contains(&longitude) {
+        return 32;
+    }
+
+    if (72.0..=84.0).contains(&latitude) && (0.0..42.0).contains(&longitude) {
+        return match longitude {
+            l if l < 9.0 => 31,
+            l if l < 21.0 => 33,
+            l if l < 33.0 => 35,
+            _ => 37,
+        };
+    }
+
+    let zone = unsafe { u32::from_unchecked(((longitude + 180.0) / 6.0).floor()) };
+    zone.min(59) + 1
+}
+
+/// Transverse Mercator projection about a central meridian, on the ellipsoid of the
+/// locations it is applied to.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TransverseMercator {
+    central_meridian: f64,
+}
+
+impl TransverseMercator {
+    /// Central meridian in degrees.
+    pub fn new(central_meridian: f64) -> Self {
+        TransverseMercator { central_meridian }
+    }
+
+    /// Projection of the UTM zone containing `location`.
+    pub fn utm(location: &GeodeticLocation) -> Self {
+        let zone = unsafe { f64::from_unchecked(utm_zone(location)) };
+        TransverseMercator::new(6.0 * zone - 183.0)
+    }
+
+    /// Central meridian in degrees.
+    pub fn central_meridian(&self) -> f64 {
+        self.central_meridian
+    }
+
+    /// Meridian convergence in radians, the bearing of grid north from true north, from
+    /// the fourth-order Krüger series.
+    ///
+    ///## References
+    ///* [Karney, C. F. F., “Transverse Mercator with an accuracy of a few nanometers”, Journal of Geodesy, 85, 475–485, 2011](https://doi.org/10.1007/s00190-011-0445-3)
+    pub fn convergence(&self, location: &GeodeticLocation) -> f64 {
+        let f = location.ellipsoid.f();
+        let e = location.ellipsoid.e_2().sqrt();
+        let n = f / (2.0 - f);
+        let alpha = [
+            n / 2.0 - 2.0 * n.powi(2) / 3.0 + 5.0 * n.powi(3) / 16.0 + 41.0 * n.powi(4) / 180.0,
+            13.0 * n.powi(2) / 48.0 - 3.0 * n.powi(3) / 5.0 + 557.0 * n.powi(4) / 1440.0,
+            61.0 * n.powi(3) / 240.0 - 103.0 * n.powi(4) / 140.0,
+            49561.0 * n.powi(4) / 161280.0,
+        ];
+
+        let lambda = wrap(location.longitude - self.central_meridian.to_radians());
+        let (sin_l, cos_l) = (lambda.sin(), lambda.cos());
+
+        let tau = location.latitude.tan();
+        let sigma = (e * (e * tau / (1.0 + tau.powi(2)).sqrt()).atanh()).sinh();
+        let tau_c = tau * (1.0 + sigma.powi(2)).sqrt() - sigma * (1.0 + tau.powi(2)).sqrt();
+
+        let xi = tau_c.atan2(cos_l);
+        let eta = (sin_l / (tau_c.powi(2) + cos_l.powi(2)).sqrt()).asinh();
+
+        let (mut p, mut q) = (1.0, 0.0);
+        for (j, a) in alpha.iter().enumerate() {
+            let k = 2.0 * unsafe { f64::from_unchecked(j + 1) };
+            p += k * a * (k * xi).cos() * (k * eta).cosh();
+            q += k * a * (k * xi).sin() * (k * eta).sinh();
+        }
+
+        let gamma = (tau_c * sin_l).atan2((1.0 + tau_c.powi(2)).sqrt() * cos_l);
+        wrap(gamma + q.atan2(p))
+    }
+}
@@ -2,20 +2,6 @@ pub trait NumFrom<T>: Sized {
     unsafe fn from_unchecked(value: T) -> Self;
 }
 
-pub trait NumInto<T>: Sized {
-    unsafe fn into_unchecked(self) -> T;
-}
-
-impl<U, T> NumInto<U> for T
-where
-    U: NumFrom<T>,
-{
-    #[inline]
-    unsafe fn into_unchecked(self) -> U {
-        U::from_unchecked(self)
-    }
-}
-
 impl NumFrom<usize> for f64 {
     #[inline]
     unsafe fn from_unchecked(value: usize) -> f64 {
@@ -43,3 +29,17 @@ impl NumFrom<f64> for usize {
         value as usize
     }
 }
+
+impl NumFrom<f64> for i16 {
+    #[inline]
+    unsafe fn from_unchecked(value: f64) -> i16 {
+        value as i16
+    }
+}
+
+impl NumFrom<f64> for u32 {
+    #[inline]
+    unsafe fn from_unchecked(value: f64) -> u32 {
+        value as u32
+    }
+}
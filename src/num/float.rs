@@ -1,96 +1,80 @@
-#[cfg(feature = "libm")]
 use libm::Libm;
 
+// The unit test harness links std, whose inherent methods take precedence there.
+#[cfg_attr(test, allow(dead_code))]
 pub trait Float: Sized {
     fn floor(self) -> Self;
+    #[cfg(feature = "geoid")]
+    fn round(self) -> Self;
     fn sin(self) -> Self;
     fn cos(self) -> Self;
     fn tan(self) -> Self;
     fn asin(self) -> Self;
-    fn atan(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn sinh(self) -> Self;
+    fn cosh(self) -> Self;
+    fn asinh(self) -> Self;
+    fn atanh(self) -> Self;
     fn sqrt(self) -> Self;
     fn powf(self, n: Self) -> Self;
     fn powi(self, n: i32) -> Self;
 }
 
-#[cfg(not(feature = "libm"))]
 impl Float for f64 {
     #[inline]
     fn floor(self) -> Self {
-        self.floor()
+        Libm::<f64>::floor(self)
+    }
+
+    #[cfg(feature = "geoid")]
+    #[inline]
+    fn round(self) -> Self {
+        Libm::<f64>::round(self)
     }
 
     #[inline]
     fn sin(self) -> Self {
-        self.sin()
+        Libm::<f64>::sin(self)
     }
 
     #[inline]
     fn cos(self) -> Self {
-        self.cos()
+        Libm::<f64>::cos(self)
     }
 
     #[inline]
     fn tan(self) -> Self {
-        self.tan()
+        Libm::<f64>::tan(self)
     }
 
     #[inline]
     fn asin(self) -> Self {
-        self.asin()
-    }
-
-    #[inline]
-    fn atan(self) -> Self {
-        self.atan()
-    }
-
-    #[inline]
-    fn sqrt(self) -> Self {
-        self.sqrt()
-    }
-
-    #[inline]
-    fn powf(self, n: Self) -> Self {
-        self.powf(n)
-    }
-
-    #[inline]
-    fn powi(self, n: i32) -> Self {
-        self.powi(n)
-    }
-}
-
-#[cfg(feature = "libm")]
-impl Float for f64 {
-    #[inline]
-    fn floor(self) -> Self {
-        Libm::<f64>::floor(self)
+        Libm::<f64>::asin(self)
     }
 
     #[inline]
-    fn sin(self) -> Self {
-        Libm::<f64>::sin(self)
+    fn atan2(self, other: Self) -> Self {
+        Libm::<f64>::atan2(self, other)
     }
 
     #[inline]
-    fn cos(self) -> Self {
-        Libm::<f64>::cos(self)
+    fn sinh(self) -> Self {
+        Libm::<f64>::sinh(self)
     }
 
     #[inline]
-    fn tan(self) -> Self {
-        Libm::<f64>::tan(self)
+    fn cosh(self) -> Self {
+        Libm::<f64>::cosh(self)
     }
 
     #[inline]
-    fn asin(self) -> Self {
-        Libm::<f64>::asin(self)
+    fn asinh(self) -> Self {
+        Libm::<f64>::asinh(self)
     }
 
     #[inline]
-    fn atan(self) -> Self {
-        Libm::<f64>::atan(self)
+    fn atanh(self) -> Self {
+        Libm::<f64>::atanh(self)
     }
 
     #[inline]
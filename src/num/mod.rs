@@ -1,5 +1,7 @@
-pub use convert::{NumFrom, NumInto};
+pub use convert::NumFrom;
+#[cfg(feature = "libm")]
 pub use float::Float;
 
 mod convert;
+#[cfg(feature = "libm")]
 mod float;
@@ -0,0 +1,156 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::num::NumFrom;
+
+/// Clamped knot vector for B-splines of the given order, with the first and last break
+/// repeated `order - 1` times.
+pub(crate) fn augment_breaks(breaks: &[f64], order: usize) -> Vec<f64> {
+    let first = breaks[0];
+    let last = breaks[breaks.len() - 1];
+
+    let mut knots = Vec::with_capacity(breaks.len() + 2 * (order - 1));
+    knots.extend(core::iter::repeat_n(first, order - 1));
+    knots.extend_from_slice(breaks);
+    knots.extend(core::iter::repeat_n(last, order - 1));
+    knots
+}
+
+/// Index `i` of the knot span with `knots[i] <= x < knots[i + 1]`, clamped so that the
+/// last break belongs to the last non-empty span.
+pub(crate) fn find_span(knots: &[f64], order: usize, x: f64) -> usize {
+    let p = order - 1;
+    let n = knots.len() - order;
+
+    if x >= knots[n] {
+        return n - 1;
+    }
+
+    if x <= knots[p] {
+        return p;
+    }
+
+    let (mut low, mut high) = (p, n);
+    while high - low > 1 {
+        let mid = (low + high) / 2;
+        if x < knots[mid] {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    low
+}
+
+///## References
+///* [Piegl, L., Tiller, W., “The NURBS Book”, Springer, 1997](https://doi.org/10.1007/978-3-642-59223-2), A2.2 and A2.3
+///
+/// Returns the `order` non-zero basis functions on `span`, and their first derivatives.
+pub(crate) fn basis(knots: &[f64], order: usize, span: usize, x: f64) -> (Vec<f64>, Vec<f64>) {
+    let p = order - 1;
+    let mut n = vec![0.0; order];
+    let mut dn = vec![0.0; order];
+    let mut left = vec![0.0; order];
+    let mut right = vec![0.0; order];
+
+    n[0] = 1.0;
+    for j in 1..=p {
+        if j == p {
+            // Degree p - 1 functions span - p + 1..=span, zero outside.
+            for r in 0..=p {
+                let lower = if r > 0 {
+                    let d = knots[span + r] - knots[span + r - p];
+                    n[r - 1] / d
+                } else {
+                    0.0
+                };
+                let upper = if r < p {
+                    let d = knots[span + r + 1] - knots[span + r + 1 - p];
+                    n[r] / d
+                } else {
+                    0.0
+                };
+
+                dn[r] = unsafe { f64::from_unchecked(p) } * (lower - upper);
+            }
+        }
+
+        left[j] = x - knots[span + 1 - j];
+        right[j] = knots[span + j] - x;
+
+        let mut saved = 0.0;
+        for r in 0..j {
+            let temp = n[r] / (right[r + 1] + left[j - r]);
+            n[r] = saved + right[r + 1] * temp;
+            saved = left[j - r] * temp;
+        }
+        n[j] = saved;
+    }
+
+    (n, dn)
+}
+
+/// Least-squares B-spline coefficients through the samples `values[i]` at `x[i]`,
+/// solved column by column with a Cholesky factorisation of the normal equations.
+pub(crate) fn fit(knots: &[f64], order: usize, x: &[f64], values: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let size = knots.len() - order;
+    let cols = values[0].len();
+
+    let mut ata = vec![vec![0.0; size]; size];
+    let mut aty = vec![vec![0.0; cols]; size];
+
+    for (xi, yi) in x.iter().zip(values) {
+        let span = find_span(knots, order, *xi);
+        let (n, _) = basis(knots, order, span, *xi);
+        let first = span + 1 - order;
+
+        for (a, na) in n.iter().enumerate() {
+            for (b, nb) in n.iter().enumerate() {
+                ata[first + a][first + b] += na * nb;
+            }
+
+            for (c, y) in yi.iter().enumerate() {
+                aty[first + a][c] += na * y;
+            }
+        }
+    }
+
+    for j in 0..size {
+        let d = ata[j][j] - ata[j][..j].iter().map(|v| v.powi(2)).sum::<f64>();
+        let d = d.sqrt();
+        ata[j][j] = d;
+
+        for i in (j + 1)..size {
+            let s: f64 = ata[i][..j]
+                .iter()
+                .zip(&ata[j][..j])
+                .map(|(a, b)| a * b)
+                .sum();
+            ata[i][j] = (ata[i][j] - s) / d;
+        }
+    }
+
+    for i in 0..size {
+        let (done, rest) = aty.split_at_mut(i);
+        let row = &mut rest[0];
+        for (l, prev) in ata[i][..i].iter().zip(done.iter()) {
+            row.iter_mut().zip(prev).for_each(|(y, p)| *y -= l * p);
+        }
+        row.iter_mut().for_each(|y| *y /= ata[i][i]);
+    }
+
+    for i in (0..size).rev() {
+        let (head, done) = aty.split_at_mut(i + 1);
+        let row = &mut head[i];
+        for (k, next) in done.iter().enumerate() {
+            let l = ata[i + 1 + k][i];
+            row.iter_mut().zip(next).for_each(|(y, p)| *y -= l * p);
+        }
+        row.iter_mut().for_each(|y| *y /= ata[i][i]);
+    }
+
+    aty
+}
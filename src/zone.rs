@@ -0,0 +1,177 @@
+use alloc::vec::Vec;
+
+use crate::error::Error;
+use crate::grid::{Grid, Span};
+use crate::location::GeodeticLocation;
+#[cfg(feature = "libm")]
+use crate::num::Float;
+use crate::num::NumFrom;
+use crate::Geomag;
+
+/// Compass usability by horizontal intensity, as defined by the WMM specification.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Zone {
+    Normal,
+    /// `h` below 6000 nT, where compasses are degraded.
+    Caution,
+    /// `h` below 2000 nT, where compasses are unreliable.
+    Blackout,
+}
+
+impl Zone {
+    pub const BLACKOUT_H: f64 = 2000.0;
+    pub const CAUTION_H: f64 = 6000.0;
+
+    /// Classifies a horizontal intensity in nT.
+    pub fn from_h(h: f64) -> Self {
+        if h < Zone::BLACKOUT_H {
+            Zone::Blackout
+        } else if h < Zone::CAUTION_H {
+            Zone::Caution
+        } else {
+            Zone::Normal
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hemisphere {
+    North,
+    South,
+}
+
+/// Dip pole of one hemisphere, where `h` is smallest, and the blackout and caution zone
+/// boundaries around it, one entry per bearing from the pole. An entry is `None` when `h`
+/// stays below the threshold within 60° of the pole along that bearing.
+#[derive(Debug, Clone)]
+pub struct ZoneBoundaries {
+    pub pole: GeodeticLocation,
+    pub blackout: Vec<Option<GeodeticLocation>>,
+    pub caution: Vec<Option<GeodeticLocation>>,
+}
+
+/// Angular distance at which the search for a boundary gives up.
+const MAX_DISTANCE: f64 = 60.0;
+
+pub(crate) fn boundaries<T: Geomag + Copy>(
+    model: T,
+    hemisphere: Hemisphere,
+    height: f64,
+    bearings: usize,
+) -> Result<ZoneBoundaries, Error> {
+    let h_at = |l: &GeodeticLocation| model.at_location(l).h;
+    let pole = dip_pole(model, hemisphere, height)?;
+
+    let mut blackout = Vec::with_capacity(bearings);
+    let mut caution = Vec::with_capacity(bearings);
+    for k in 0..bearings {
+        let bearing = unsafe { f64::from_unchecked(k) / f64::from_unchecked(bearings) }
+            * core::f64::consts::TAU;
+
+        for (threshold, points) in [
+            (Zone::BLACKOUT_H, &mut blackout),
+            (Zone::CAUTION_H, &mut caution),
+        ] {
+            let delta = crossing(|delta| h_at(&destination(&pole, bearing, delta)), threshold);
+            points.push(delta.map(|delta| destination(&pole, bearing, delta)));
+        }
+    }
+
+    Ok(ZoneBoundaries {
+        pole,
+        blackout,
+        caution,
+    })
+}
+
+/// Minimises `h` over a coarse grid of the hemisphere, then refines the minimum with a
+/// pattern search until it moves by less than a metre.
+fn dip_pole<T: Geomag + Copy>(
+    model: T,
+    hemisphere: Hemisphere,
+    height: f64,
+) -> Result<GeodeticLocation, Error> {
+    let latitude = match hemisphere {
+        Hemisphere::North => Span::new(50.0, 90.0, 1.0),
+        Hemisphere::South => Span::new(-90.0, -50.0, 1.0),
+    };
+    let grid = Grid::new(
+        latitude,
+        Span::new(-180.0, 175.0, 5.0),
+        Span::single(height),
+    )?;
+
+    let hs = model.at_grid_map(&grid, |m| m.h);
+    let (i, mut best) =
+        hs.into_iter().enumerate().fold(
+            (0, f64::INFINITY),
+            |a, (i, h)| if h < a.1 { (i, h) } else { a },
+        );
+
+    let mut pole = grid.location(i);
+    let mut step = 1.0_f64.to_radians();
+    while step > 1e-7 {
+        let next = (0..8_usize)
+            .map(|k| {
+                let bearing = unsafe { f64::from_unchecked(k) } * core::f64::consts::FRAC_PI_4;
+                let l = destination(&pole, bearing, step);
+                (model.at_location(&l).h, l)
+            })
+            .fold(
+                (best, None),
+                |a, (h, l)| if h < a.0 { (h, Some(l)) } else { a },
+            );
+
+        match next {
+            (h, Some(l)) => {
+                best = h;
+                pole = l;
+            }
+            _ => step *= 0.5,
+        }
+    }
+
+    Ok(pole)
+}
+
+/// Angular distance in radians at which `h(delta)` first rises through `threshold`,
+/// stepping outwards and then bisecting, or `None` if it does not within [`MAX_DISTANCE`].
+fn crossing<F: Fn(f64) -> f64>(h: F, threshold: f64) -> Option<f64> {
+    let step = 0.25_f64.to_radians();
+    let mut lower = 0.0;
+    let mut upper = step;
+    while h(upper) < threshold {
+        lower = upper;
+        upper += step;
+        if upper > MAX_DISTANCE.to_radians() {
+            return None;
+        }
+    }
+
+    for _ in 0..40 {
+        let mid = 0.5 * (lower + upper);
+        if h(mid) < threshold {
+            lower = mid;
+        } else {
+            upper = mid;
+        }
+    }
+
+    Some(0.5 * (lower + upper))
+}
+
+/// Point at angular distance `delta` along `bearing` from `from`, on a sphere.
+fn destination(from: &GeodeticLocation, bearing: f64, delta: f64) -> GeodeticLocation {
+    let (sin_p, cos_p) = (from.latitude.sin(), from.latitude.cos());
+    let (sin_d, cos_d) = (delta.sin(), delta.cos());
+
+    let sin_lat = (sin_p * cos_d + cos_p * sin_d * bearing.cos()).clamp(-1.0, 1.0);
+    let latitude = sin_lat.asin();
+    let longitude = from.longitude + (bearing.sin() * sin_d * cos_p).atan2(cos_d - sin_p * sin_lat);
+
+    GeodeticLocation {
+        longitude: longitude.sin().atan2(longitude.cos()),
+        latitude,
+        ..*from
+    }
+}
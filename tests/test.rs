@@ -11,7 +11,7 @@ fn decimal() {
 #[test]
 #[cfg(feature = "wmm")]
 fn wmm() {
-    let l = GeodeticLocation::new(102.0, 24.0, 1900.0);
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
     let wmm = WMM::new(2023.8328767123287).unwrap();
     let m = wmm.at_location(&l);
 
@@ -34,68 +34,1584 @@ fn wmm() {
 #[test]
 #[cfg(feature = "igrf")]
 fn igrf() {
-    let l = GeodeticLocation::new(102.0, 24.0, 1900.0);
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
     let igrf = IGRF::new(2023.8328767123287).unwrap();
     let m = igrf.at_location(&l);
 
-    assert!(m.x - 37634.0 < 1.0);
-    assert!(m.x_dot - 1.0 < 1.0);
-    assert!(m.y - (-1103.0) < 1.0);
-    assert!(m.y_dot - (-40.0) < 1.0);
-    assert!(m.z - 28846.0 < 1.0);
-    assert!(m.z_dot - 112.0 < 1.0);
-    assert!(m.h - 37650.0 < 1.0);
-    assert!(m.h_dot - 2.0 < 1.0);
-    assert!(m.f - 47430.0 < 1.0);
-    assert!(m.f_dot - 70.0 < 1.0);
+    assert!(m.x - 37616.0 < 1.0);
+    assert!(m.x_dot - (-4.0) < 1.0);
+    assert!(m.y - (-984.0) < 1.0);
+    assert!(m.y_dot - (-10.0) < 1.0);
+    assert!(m.z - 28697.0 < 1.0);
+    assert!(m.z_dot - 73.0 < 1.0);
+    assert!(m.h - 37629.0 < 1.0);
+    assert!(m.h_dot - (-3.0) < 1.0);
+    assert!(m.f - 47323.0 < 1.0);
+    assert!(m.f_dot - 42.0 < 1.0);
     assert!(m.d - (-0.02) < 0.01);
     assert!(m.d_dot - (-0.001) < 0.001);
     assert!(m.i - 0.6 < 0.1);
     assert!(m.i_dot - 0.001 < 0.001);
 }
 
+/// Field at the geographic north pole, longitude 0, summed in closed form.
+///
+/// At the pole only the zonal and `m = 1` terms survive: with `c = (a / r)^(n + 2)`,
+/// `x = sum c sqrt(n (n + 1) / 2) g(n, 1)`, `y = -sum c sqrt(n (n + 1) / 2) h(n, 1)` and
+/// `z = -sum c (n + 1) g(n, 0)`, where `r` is the polar radius plus `height`.
+#[cfg(any(feature = "wmm", feature = "igrf"))]
+fn north_pole<M: Model>(model: &M, height: f64) -> [f64; 6] {
+    let a = 6371200.0;
+    let r = 6356752.314245 + height;
+    let dt = model.t() - model.t0();
+    let mut field = [0.0; 6];
+
+    for n in 1..=model.deg() {
+        let c = (a / r).powi(n as i32 + 2);
+        let k = (n as f64 * (n as f64 + 1.0) / 2.0).sqrt();
+        let nf = n as f64 + 1.0;
+        let (g0, g1, h1) = (model.g(n, 0), model.g(n, 1), model.h(n, 1));
+        let (g0_sv, g1_sv, h1_sv) = (model.g_sv(n, 0), model.g_sv(n, 1), model.h_sv(n, 1));
+
+        field[0] += c * k * (g1 + dt * g1_sv);
+        field[1] -= c * k * (h1 + dt * h1_sv);
+        field[2] -= c * nf * (g0 + dt * g0_sv);
+        field[3] += c * k * g1_sv;
+        field[4] -= c * k * h1_sv;
+        field[5] -= c * nf * g0_sv;
+    }
+
+    field
+}
+
+#[cfg(any(feature = "wmm", feature = "igrf"))]
+fn assert_pole(m: &MagneticField, [x, y, z, x_dot, y_dot, z_dot]: [f64; 6]) {
+    let h = x.hypot(y);
+    let f = h.hypot(z);
+    let h_dot = (x * x_dot + y * y_dot) / h;
+    let f_dot = (x * x_dot + y * y_dot + z * z_dot) / f;
+
+    assert!((m.x - x).abs() < 0.01);
+    assert!((m.y - y).abs() < 0.01);
+    assert!((m.z - z).abs() < 0.01);
+    assert!((m.x_dot - x_dot).abs() < 0.01);
+    assert!((m.y_dot - y_dot).abs() < 0.01);
+    assert!((m.z_dot - z_dot).abs() < 0.01);
+    assert!((m.h - h).abs() < 0.01);
+    assert!((m.f - f).abs() < 0.01);
+    assert!((m.h_dot - h_dot).abs() < 0.01);
+    assert!((m.f_dot - f_dot).abs() < 0.01);
+    assert!((m.d - y.atan2(x)).abs() < 1e-6);
+    assert!((m.i - z.atan2(h)).abs() < 1e-6);
+    assert!((m.d_dot - (x * y_dot - y * x_dot) / h.powi(2)).abs() < 1e-6);
+    assert!((m.i_dot - (h * z_dot - z * h_dot) / f.powi(2)).abs() < 1e-6);
+}
+
 #[test]
 #[cfg(feature = "wmm")]
 fn wmm_at_pole() {
-    let l = GeodeticLocation::new(0.0, 90.0, 1900.0);
+    let l = GeodeticLocation::new(0.0, 90.0, 1900.0).unwrap();
     let wmm = WMM::new(2023.8328767123287).unwrap();
     let m = wmm.at_location(&l);
 
-    assert!(m.x - 1717.0 < 1.0);
-    assert!(m.x_dot - (-27.0) < 1.0);
-    assert!(m.y - 358.0 < 1.0);
-    assert!(m.y_dot - 63.0 < 1.0);
-    assert!(m.z - 56776.0 < 1.0);
-    assert!(m.z_dot - 24.0 < 1.0);
-    assert!(m.h - 1754.0 < 1.0);
-    assert!(m.h_dot - (-14.0) < 1.0);
-    assert!(m.f - 56803.0 < 1.0);
-    assert!(m.f_dot - 23.0 < 1.0);
-    assert!(m.d - 0.2 < 0.1);
-    assert!(m.d_dot - 0.03 < 0.01);
-    assert!(m.i - 1.0 < 1.0);
-    assert!(m.i_dot - 0.0002 < 0.0001);
+    assert_pole(&m, north_pole(&wmm, 1900.0));
+    assert!((m.x - 1714.0).abs() < 1.0);
+    assert!((m.y - 358.0).abs() < 1.0);
+    assert!((m.z - 56776.0).abs() < 1.0);
 }
 
 #[test]
 #[cfg(feature = "igrf")]
 fn igrf_at_pole() {
-    let l = GeodeticLocation::new(0.0, 90.0, 1900.0);
+    let l = GeodeticLocation::new(0.0, 90.0, 1900.0).unwrap();
     let igrf = IGRF::new(2023.8328767123287).unwrap();
     let m = igrf.at_location(&l);
 
-    assert!(m.x - 1711.0 < 1.0);
-    assert!(m.x_dot - (-24.0) < 1.0);
-    assert!(m.y - 364.0 < 1.0);
-    assert!(m.y_dot - 62.0 < 1.0);
-    assert!(m.z - 56778.0 < 1.0);
-    assert!(m.z_dot - 25.0 < 1.0);
-    assert!(m.h - 1749.0 < 1.0);
-    assert!(m.h_dot - (-10.0) < 1.0);
-    assert!(m.f - 56805.0 < 1.0);
-    assert!(m.f_dot - 24.0 < 1.0);
-    assert!(m.d - 0.2 < 0.1);
-    assert!(m.d_dot - 0.03 < 0.01);
-    assert!(m.i - 1.0 < 1.0);
-    assert!(m.i_dot - 0.0002 < 0.0001);
+    assert_pole(&m, north_pole(&igrf, 1900.0));
+    assert!((m.x - 1746.0).abs() < 1.0);
+    assert!((m.y - 366.0).abs() < 1.0);
+    assert!((m.z - 56779.0).abs() < 1.0);
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn wmm_from_cof() {
+    let content = format!(
+        "    2020.0            WMM-2020        12/10/2019\n{}{}\n{}\n",
+        include_str!("../data/WMM2020.COF"),
+        "999999999999999999999999999999999999999999999999",
+        "999999999999999999999999999999999999999999999999"
+    );
+
+    let cof: Cof = content.parse().unwrap();
+    assert_eq!(cof.epoch, 2020.0);
+    assert_eq!(cof.name, "WMM-2020");
+    assert_eq!(cof.release_date, "12/10/2019");
+    assert_eq!(cof.deg(), 12);
+
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let wmm = WMM::new(2023.8328767123287).unwrap();
+    let wmm_cof = WMM::from_cof(&cof, 2023.8328767123287).unwrap();
+    let m = wmm.at_location(&l);
+    let m_cof = wmm_cof.at_location(&l);
+
+    assert_eq!(m.x, m_cof.x);
+    assert_eq!(m.y, m_cof.y);
+    assert_eq!(m.z, m_cof.z);
+    assert!(WMM::from_cof(&cof, 2025.5).is_err());
+
+    let wmm_str = WMM::from_cof_str(&content, 2023.8328767123287).unwrap();
+    assert_eq!(wmm_str.at_location(&l).x, m.x);
+    assert!(matches!(
+        WMM::from_cof_str("2020.0 WMM-2020\n", 2020.0),
+        Err(Error::Parse(_))
+    ));
+
+    #[cfg(not(feature = "libm"))]
+    {
+        let cof = Cof::from_reader(content.as_bytes()).unwrap();
+        assert_eq!(cof.deg(), 12);
+        let wmm_reader = WMM::from_reader(content.as_bytes(), 2023.8328767123287).unwrap();
+        assert_eq!(wmm_reader.at_location(&l).x, m.x);
+    }
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn wmm_from_cof_error() {
+    let content = "    2020.0            WMM-2020        12/10/2019\n\
+                   \x20 1  0  -29404.5       0.0        6.7        0.0\n\
+                   \x20 1  1   -1450.7    abc           7.7      -25.1\n";
+    let err = content.parse::<Cof>().unwrap_err();
+    assert_eq!(err.line(), 3);
+    assert_eq!(err.kind(), &ParseErrorKind::InvalidNumber);
+
+    let content = "    2020.0            WMM-2020        12/10/2019\n\
+                   \x20 1  0  -29404.5       0.0        6.7        0.0\n\
+                   \x20 1  1   -1450.7    4652.9        7.7      -25.1\n";
+    let err = content.parse::<Cof>().unwrap_err();
+    assert_eq!(err.line(), 3);
+    assert_eq!(err.kind(), &ParseErrorKind::MissingTerminator);
+}
+
+#[cfg(feature = "igrf")]
+fn igrf_shc() -> String {
+    let mut content =
+        String::from("# IGRF converted from igrf14coeffs.txt\n   1  13  25   2   1\n");
+    for i in 0..25 {
+        content += &format!(" {:.1}", 1900.0 + 5.0 * i as f64);
+    }
+    content.push('\n');
+
+    for line in include_str!("../data/igrf14coeffs.txt").lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if !matches!(fields[0], "g" | "h") {
+            continue;
+        }
+        let m = if fields[0] == "h" {
+            format!("-{}", fields[2])
+        } else {
+            fields[2].to_string()
+        };
+        content += &format!("{} {} {}\n", fields[1], m, fields[3..28].join(" "));
+    }
+
+    content
+}
+
+#[test]
+#[cfg(feature = "igrf")]
+fn shc_linear() {
+    let shc: Shc = igrf_shc().parse().unwrap();
+    assert_eq!(shc.nmin(), 1);
+    assert_eq!(shc.nmax(), 13);
+    assert_eq!(shc.times().len(), 25);
+
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    for t in [1902.5, 1987.3, 2013.3, 2019.9] {
+        let m = IGRF::new(t).unwrap().at_location(&l);
+        let m_shc = ShcModel::from_shc(&shc, t).unwrap().at_location(&l);
+
+        assert!((m.x - m_shc.x).abs() < 1e-6);
+        assert!((m.y - m_shc.y).abs() < 1e-6);
+        assert!((m.z - m_shc.z).abs() < 1e-6);
+        assert!((m.x_dot - m_shc.x_dot).abs() < 1e-6);
+        assert!((m.z_dot - m_shc.z_dot).abs() < 1e-6);
+    }
+
+    assert!(ShcModel::from_shc(&shc, 2020.5).is_err());
+}
+
+#[test]
+fn shc_spline() {
+    let g = |t: f64| -30000.0 + 10.0 * (t - 2000.0) + 0.5 * (t - 2000.0).powi(2);
+    let times = [2000.0, 2001.0, 2002.0, 2003.0, 2004.0, 2005.0, 2006.0];
+
+    let mut content = String::from("1 1 7 3 2\n");
+    for t in times {
+        content += &format!("{} ", t);
+    }
+    content += "\n1 0";
+    for t in times {
+        content += &format!(" {}", g(t));
+    }
+    content += "\n1 1 0 0 0 0 0 0 0\n1 -1 0 0 0 0 0 0 0\n";
+    let shc: Shc = content.parse().unwrap();
+
+    let t = 2003.7;
+    let l = GeodeticLocation::new(0.0, 0.0, 0.0).unwrap();
+    let m = ShcModel::from_shc(&shc, t).unwrap().at_location(&l);
+    let fixed = format!("1 1 1 1 1\n{}\n1 0 {}\n1 1 0\n1 -1 0\n", t, g(t));
+    let fixed: Shc = fixed.parse().unwrap();
+    let m_fixed = ShcModel::from_shc(&fixed, t).unwrap().at_location(&l);
+
+    assert!((m.x - m_fixed.x).abs() < 1e-6);
+    assert!((m.x_dot / m.x - (10.0 + (t - 2000.0)) / g(t)).abs() < 1e-9);
+}
+
+#[test]
+fn shc_error() {
+    let content = "# comment\n1 1 2 2 1\n2000.0 2005.0\n1 0 -29000 -29100\n1 2 0 0\n";
+    let err = content.parse::<Shc>().unwrap_err();
+    assert_eq!(err.line(), 5);
+    assert_eq!(err.kind(), &ParseErrorKind::UnexpectedTerm { n: 1, m: 2 });
+
+    let content = "1 1 2 2 1\n2000.0 2005.0\n1 0 -29000 -29100\n1 1 0 0\n";
+    let err = content.parse::<Shc>().unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::IncompleteDegree(1));
+
+    let content = "1 1 3 2 1\n2000 2000 2010\n1 0 1 2 3\n1 1 1 2 3\n-1 1 1 2 3\n";
+    let err = content.parse::<Shc>().unwrap_err();
+    assert_eq!(
+        (err.line(), err.kind()),
+        (2, &ParseErrorKind::UnorderedTimes)
+    );
+
+    let content = "# times\n1 1 4 2 1\n2000 1990 2010 2020\n1 0 1 2 3 4\n";
+    let err = content.parse::<Shc>().unwrap_err();
+    assert_eq!(
+        (err.line(), err.kind()),
+        (3, &ParseErrorKind::UnorderedTimes)
+    );
+}
+
+struct Dipole {
+    g10: f64,
+    g10_sv: f64,
+}
+
+impl Model for Dipole {
+    fn deg(&self) -> usize {
+        1
+    }
+
+    fn t0(&self) -> f64 {
+        2020.0
+    }
+
+    fn t(&self) -> f64 {
+        2022.0
+    }
+
+    fn g(&self, n: usize, m: usize) -> f64 {
+        if (n, m) == (1, 0) {
+            self.g10
+        } else {
+            0.0
+        }
+    }
+
+    fn h(&self, _n: usize, _m: usize) -> f64 {
+        0.0
+    }
+
+    fn g_sv(&self, n: usize, m: usize) -> f64 {
+        if (n, m) == (1, 0) {
+            self.g10_sv
+        } else {
+            0.0
+        }
+    }
+
+    fn h_sv(&self, _n: usize, _m: usize) -> f64 {
+        0.0
+    }
+}
+
+#[test]
+fn custom_model() {
+    let dipole = Dipole {
+        g10: -30000.0,
+        g10_sv: 10.0,
+    };
+    let l = GeodeticLocation::new(0.0, 0.0, 0.0).unwrap();
+    let m = dipole.at_location(&l);
+    let f = (6371200.0_f64 / 6378137.0).powi(3);
+
+    assert!((m.x - 29980.0 * f).abs() < 1e-6);
+    assert!((m.x_dot + 10.0 * f).abs() < 1e-6);
+    assert!(m.y.abs() < 1e-6);
+    assert!(m.z.abs() < 1e-6);
+
+    let model: &dyn Model = &dipole;
+    let m_dyn = model.at_location(&l);
+    assert_eq!(m.x, m_dyn.x);
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn wmm_release() {
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+
+    assert_eq!(WmmRelease::containing(2023.8), Some(WmmRelease::Wmm2020));
+    assert_eq!(WmmRelease::containing(2025.0), Some(WmmRelease::Wmm2025));
+    assert_eq!(WmmRelease::containing(2031.0), None);
+    assert!(WMM::release(WmmRelease::Wmm2020, 2026.0).is_err());
+
+    let m = WMM::new(2026.5).unwrap().at_location(&l);
+    let m_release = WMM::release(WmmRelease::Wmm2025, 2026.5)
+        .unwrap()
+        .at_location(&l);
+    assert_eq!(m.x, m_release.x);
+
+    let m_2020 = WMM::release(WmmRelease::Wmm2020, 2025.0)
+        .unwrap()
+        .at_location(&l);
+    let m_2025 = WMM::release(WmmRelease::Wmm2025, 2025.0)
+        .unwrap()
+        .at_location(&l);
+    assert!((m_2020.f - m_2025.f).abs() < 250.0);
+    assert!((m_2020.d - m_2025.d).abs() < 0.01);
+}
+
+#[test]
+#[cfg(all(feature = "igrf", feature = "wmm"))]
+fn igrf_14() {
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+
+    assert!(IGRF::new(1899.9).is_err());
+    assert!(IGRF::new(2030.1).is_err());
+
+    let before = IGRF::new(2024.999999).unwrap().at_location(&l);
+    let at = IGRF::new(2025.0).unwrap().at_location(&l);
+    let end = IGRF::new(2030.0).unwrap().at_location(&l);
+    assert!((before.x - at.x).abs() < 0.01);
+    assert!((before.z - at.z).abs() < 0.01);
+    assert!((end.x - (at.x + 5.0 * at.x_dot)).abs() < 1e-6);
+
+    let wmm = WMM::new(2025.0).unwrap().at_location(&l);
+    assert!((wmm.f - at.f).abs() < 50.0);
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        DateTime::new(2023, 2, 29, 0, 0, 0).unwrap_err(),
+        Error::InvalidDateTime {
+            field: DateTimeField::Day,
+            value: 29
+        }
+    );
+    assert_eq!(
+        DateTime::new(2023, 13, 1, 0, 0, 0).unwrap_err(),
+        Error::InvalidDateTime {
+            field: DateTimeField::Month,
+            value: 13
+        }
+    );
+    assert!(DateTime::new(2024, 2, 29, 0, 0, 0).is_ok());
+
+    assert_eq!(
+        GeodeticLocation::new(0.0, 91.0, 0.0).unwrap_err(),
+        Error::InvalidLocation {
+            field: LocationField::Latitude,
+            value: 91.0
+        }
+    );
+    assert!(GeodeticLocation::new(0.0, 0.0, f64::NAN).is_err());
+}
+
+#[test]
+#[cfg(all(feature = "igrf", feature = "wmm"))]
+fn model_errors() {
+    assert_eq!(
+        IGRF::new(2031.0).err(),
+        Some(Error::OutOfRange {
+            model: "IGRF",
+            decimal: 2031.0,
+            start: 1900.0,
+            end: 2030.0
+        })
+    );
+    assert_eq!(
+        WMM::release(WmmRelease::Wmm2020, 2026.0).err(),
+        Some(Error::OutOfRange {
+            model: "WMM2020",
+            decimal: 2026.0,
+            start: 2020.0,
+            end: 2025.0
+        })
+    );
+    assert_eq!("wmm-2025".parse(), Ok(WmmRelease::Wmm2025));
+    assert_eq!(
+        "WMM2015".parse::<WmmRelease>(),
+        Err(Error::UnknownRelease { model: "WMM" })
+    );
+
+    let err: Error = "1 1 1 1 1\n".parse::<Shc>().unwrap_err().into();
+    assert_eq!(err.to_string(), "line 1: no coefficients found");
+}
+
+struct Single {
+    n: usize,
+    m: usize,
+    g: f64,
+}
+
+impl Model for Single {
+    fn deg(&self) -> usize {
+        self.n
+    }
+
+    fn t0(&self) -> f64 {
+        2020.0
+    }
+
+    fn t(&self) -> f64 {
+        2020.0
+    }
+
+    fn g(&self, n: usize, m: usize) -> f64 {
+        if (n, m) == (self.n, self.m) {
+            self.g
+        } else {
+            0.0
+        }
+    }
+
+    fn h(&self, _n: usize, _m: usize) -> f64 {
+        0.0
+    }
+
+    fn g_sv(&self, _n: usize, _m: usize) -> f64 {
+        0.0
+    }
+
+    fn h_sv(&self, _n: usize, _m: usize) -> f64 {
+        0.0
+    }
+}
+
+#[test]
+fn high_degree() {
+    let n = 720;
+    let model = Single { n, m: 0, g: 1.0 };
+    let l = GeodeticLocation::new(0.0, 0.0, 0.0).unwrap();
+    let m = model.at_location(&l);
+
+    // P(n, 0) at the equator is (-1)^(n/2) (n-1)!! / n!!.
+    let p = (1..=n / 2).fold(1.0, |p, k| p * (2 * k - 1) as f64 / (2 * k) as f64);
+    let f = (6371200.0_f64 / 6378137.0).powi(n as i32 + 2);
+    assert!((m.z - (-f * (n as f64 + 1.0) * p)).abs() < 1e-9);
+
+    for lat in [89.999, 45.0, -30.0] {
+        let l = GeodeticLocation::new(10.0, lat, 0.0).unwrap();
+        for m in [0, 1, 360, 720] {
+            let model = Single { n, m, g: 1.0 };
+            let field = model.at_location(&l);
+            assert!(field.x.is_finite() && field.y.is_finite() && field.z.is_finite());
+        }
+    }
+}
+
+#[test]
+fn at_pole() {
+    let model = Single {
+        n: 1,
+        m: 1,
+        g: 1000.0,
+    };
+    let l = GeodeticLocation::new(30.0, 90.0, 0.0).unwrap();
+    let m = model.at_location(&l);
+    let b = 6378137.0_f64 * (1.0 - 1.0 / 298.257223563);
+    let f = 1000.0 * (6371200.0 / b).powi(3);
+
+    assert!((m.x - f * 30_f64.to_radians().cos()).abs() < 1e-6);
+    assert!((m.y - f * 30_f64.to_radians().sin()).abs() < 1e-6);
+    assert!(m.z.abs() < 1e-6);
+
+    #[cfg(feature = "wmm")]
+    for lat in [90.0, -90.0] {
+        let wmm = WMM::new(2023.8328767123287).unwrap();
+        let at = wmm.at_location(&GeodeticLocation::new(45.0, lat, 0.0).unwrap());
+        let near = lat - lat.signum() * 1e-7;
+        let near = wmm.at_location(&GeodeticLocation::new(45.0, near, 0.0).unwrap());
+
+        assert!((at.y - near.y).abs() < 1e-3);
+        assert!((at.y_dot - near.y_dot).abs() < 1e-3);
+        assert!((at.d - near.d).abs() < 1e-6);
+        assert!((at.d_dot - near.d_dot).abs() < 1e-6);
+    }
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn declination_quadrant() {
+    let l = GeodeticLocation::new(135.0, -75.0, 0.0).unwrap();
+    let t = 2023.8328767123287;
+    let m = WMM::new(t).unwrap().at_location(&l);
+
+    assert!(m.x < 0.0);
+    assert!(m.d.abs() > core::f64::consts::FRAC_PI_2);
+    assert!((m.h * m.d.cos() - m.x).abs() < 1e-6);
+    assert!((m.h * m.d.sin() - m.y).abs() < 1e-6);
+    assert!(m.i < 0.0);
+
+    let dt = 0.01;
+    let next = WMM::new(t + dt).unwrap().at_location(&l);
+    let dd = (next.d - m.d + core::f64::consts::PI).rem_euclid(2.0 * core::f64::consts::PI)
+        - core::f64::consts::PI;
+    assert!((dd / dt - m.d_dot).abs() < 1e-4);
+    assert!(((next.i - m.i) / dt - m.i_dot).abs() < 1e-4);
+}
+
+#[test]
+fn zero_horizontal() {
+    let m = MagneticField::from_xyz(0.0, 0.0, 55000.0, 3.0, -4.0, 20.0);
+
+    assert_eq!(m.h, 0.0);
+    assert_eq!(m.h_dot, 5.0);
+    assert_eq!(m.f, 55000.0);
+    assert_eq!(m.f_dot, 20.0);
+    assert_eq!(m.d, 0.0);
+    assert_eq!(m.d_dot, 0.0);
+    assert_eq!(m.i, core::f64::consts::FRAC_PI_2);
+    assert!((m.i_dot + 5.0 / 55000.0).abs() < 1e-12);
+
+    let m = MagneticField::from_xyz(0.0, 0.0, -55000.0, 0.0, 0.0, 0.0);
+    assert_eq!(m.i, -core::f64::consts::FRAC_PI_2);
+    assert_eq!(m.d_dot, 0.0);
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn grid() {
+    let wmm = WMM::new(2025.5).unwrap();
+    let grid = Grid::new(
+        Span::new(-90.0, 90.0, 22.5),
+        Span::new(-180.0, 180.0, 45.0),
+        Span::new(0.0, 10000.0, 5000.0),
+    )
+    .unwrap();
+
+    assert_eq!(grid.len(), 9 * 9 * 3);
+
+    let fields = wmm.at_grid(&grid);
+    assert_eq!(fields.len(), grid.len());
+
+    for (i, m) in fields.iter().enumerate() {
+        let e = wmm.at_location(&grid.location(i));
+        assert!((m.x - e.x).abs() < 1e-6);
+        assert!((m.y - e.y).abs() < 1e-6);
+        assert!((m.z - e.z).abs() < 1e-6);
+        assert!((m.f_dot - e.f_dot).abs() < 1e-6);
+    }
+
+    let d = wmm.at_grid_map(&grid, |m| m.d);
+    assert!(d.iter().zip(&fields).all(|(d, m)| *d == m.d));
+
+    let single = Grid::new(
+        Span::single(24.0),
+        Span::single(102.0),
+        Span::single(1900.0),
+    )
+    .unwrap();
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    assert_eq!(single.len(), 1);
+    assert!(!single.is_empty());
+    assert!((wmm.at_grid(&single)[0].f - wmm.at_location(&l).f).abs() < 1e-6);
+
+    assert!(Grid::new(
+        Span::new(0.0, 10.0, 0.0),
+        Span::single(0.0),
+        Span::single(0.0)
+    )
+    .is_err());
+    assert!(Grid::new(
+        Span::new(10.0, 0.0, 1.0),
+        Span::single(0.0),
+        Span::single(0.0)
+    )
+    .is_err());
+    assert!(Grid::new(
+        Span::new(0.0, 91.0, 1.0),
+        Span::single(0.0),
+        Span::single(0.0)
+    )
+    .is_err());
+
+    for span in [
+        Span::new(10.0, 0.0, 1.0),
+        Span::new(0.0, 10.0, 0.0),
+        Span::new(0.0, 10.0, -1.0),
+        Span::new(0.0, f64::NAN, 1.0),
+        Span::new(0.0, 10.0, f64::INFINITY),
+    ] {
+        assert_eq!(span.len(), 0);
+        assert!(span.is_empty());
+    }
+    assert_eq!(Span::new(0.0, 1e300, 1e-300).len(), usize::MAX);
+
+    let empty = Grid {
+        latitude: Span::new(10.0, 0.0, 1.0),
+        ..single
+    };
+    assert_eq!(empty.len(), 0);
+    assert!(empty.is_empty());
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn adjusted_coefficients() {
+    struct Plain<'a>(&'a WMM);
+
+    impl Model for Plain<'_> {
+        fn deg(&self) -> usize {
+            self.0.deg()
+        }
+        fn t0(&self) -> f64 {
+            self.0.t0()
+        }
+        fn t(&self) -> f64 {
+            self.0.t()
+        }
+        fn g(&self, n: usize, m: usize) -> f64 {
+            self.0.g(n, m)
+        }
+        fn h(&self, n: usize, m: usize) -> f64 {
+            self.0.h(n, m)
+        }
+        fn g_sv(&self, n: usize, m: usize) -> f64 {
+            self.0.g_sv(n, m)
+        }
+        fn h_sv(&self, n: usize, m: usize) -> f64 {
+            self.0.h_sv(n, m)
+        }
+    }
+
+    let wmm = WMM::new(2027.5).unwrap();
+    assert!((wmm.g(1, 0) - (-29351.8)).abs() < 1e-9);
+    assert!((wmm.h(1, 1) - 4545.4).abs() < 1e-9);
+    assert_eq!(wmm.g_sv(1, 0), 12.0);
+
+    let mut moved = WMM::new(2025.0).unwrap();
+    for i in 1..=50 {
+        moved = moved.at_time(2025.0 + f64::from(i) * 0.0973).unwrap();
+    }
+    let fresh = WMM::new(moved.t()).unwrap();
+    for n in 1..=fresh.deg() {
+        for m in 0..=n {
+            assert_eq!(moved.g(n, m), fresh.g(n, m));
+            assert_eq!(moved.h(n, m), fresh.h(n, m));
+        }
+    }
+
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let m = wmm.at_location(&l);
+    let m_plain = Plain(&wmm).at_location(&l);
+    assert!((m.x - m_plain.x).abs() < 1e-6);
+    assert!((m.y - m_plain.y).abs() < 1e-6);
+    assert!((m.z - m_plain.z).abs() < 1e-6);
+    assert!((m.z_dot - m_plain.z_dot).abs() < 1e-9);
+}
+
+#[test]
+#[cfg(feature = "igrf")]
+fn igrf_time_series() {
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let igrf = IGRF::new(2003.0).unwrap();
+
+    let times = Span::new(1995.0, 2015.0, 0.5);
+    let series = igrf.time_series(&l, times).unwrap();
+    assert_eq!(series.len(), 41);
+
+    for (i, m) in series.iter().enumerate() {
+        let e = IGRF::new(times.get(i)).unwrap().at_location(&l);
+        assert!((m.x - e.x).abs() < 1e-6);
+        assert!((m.y - e.y).abs() < 1e-6);
+        assert!((m.z - e.z).abs() < 1e-6);
+        assert!((m.d_dot - e.d_dot).abs() < 1e-12);
+    }
+
+    let m = igrf.at(&l, 2023.83).unwrap();
+    let e = IGRF::new(2023.83).unwrap().at_location(&l);
+    assert!((m.f - e.f).abs() < 1e-6);
+
+    let moved = igrf.at_time(2004.5).unwrap();
+    assert_eq!(moved.t0(), 2000.0);
+    assert_eq!(moved.t(), 2004.5);
+    assert!((moved.g(1, 0) - igrf.g(1, 0)).abs() < 1e-9);
+
+    assert!(igrf.at(&l, 2031.0).is_err());
+    assert_eq!(
+        igrf.time_series(&l, Span::new(2000.0, 2010.0, 0.0)).err(),
+        Some(Error::InvalidSpan {
+            start: 2000.0,
+            end: 2010.0,
+            step: 0.0,
+        })
+    );
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn wmm_time_series() {
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let wmm = WMM::new(2025.0).unwrap();
+
+    let series = wmm.time_series(&l, Span::new(2025.0, 2030.0, 1.0)).unwrap();
+    assert_eq!(series.len(), 6);
+
+    let e = WMM::new(2028.0).unwrap().at_location(&l);
+    assert!((series[3].d - e.d).abs() < 1e-12);
+    assert!((series[3].f - e.f).abs() < 1e-6);
+
+    assert!(wmm.at_time(2024.0).is_err());
+    assert!(wmm.time_series(&l, Span::new(2028.0, 2031.0, 1.0)).is_err());
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn geocentric() {
+    let wmm = WMM::new(2025.5).unwrap();
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let m = wmm.at_location(&l);
+
+    let c = GeocentricLocation::from(&l);
+    let s = wmm.at_geocentric(&c);
+    let f = (s.r.powi(2) + s.theta.powi(2) + s.phi.powi(2)).sqrt();
+    assert!((f - m.f).abs() < 1e-6);
+    assert!((s.phi - m.y).abs() < 1e-9);
+
+    let lon = c.longitude.to_degrees();
+    let lat = c.latitude.to_degrees();
+    let r = c.radius;
+    let v = |lon: f64, lat: f64, r: f64| {
+        let c = GeocentricLocation::new(lon, lat, r).unwrap();
+        wmm.at_geocentric(&c).v
+    };
+
+    let dr = 1.0;
+    let br = -(v(lon, lat, r + dr) - v(lon, lat, r - dr)) / (2.0 * dr);
+    assert!((br - s.r).abs() < 0.01);
+
+    let da = 1e-4_f64;
+    let dv_dlat = (v(lon, lat + da, r) - v(lon, lat - da, r)) / (2.0 * da.to_radians());
+    assert!((dv_dlat / r - s.theta).abs() < 0.01);
+
+    let dv_dlon = (v(lon + da, lat, r) - v(lon - da, lat, r)) / (2.0 * da.to_radians());
+    let b_phi = -dv_dlon / (r * c.colatitude().sin());
+    assert!((b_phi - s.phi).abs() < 0.01);
+
+    assert!(GeocentricLocation::new(0.0, 0.0, 0.0).is_err());
+    assert!(GeocentricLocation::new(0.0, 91.0, 6371200.0).is_err());
+}
+
+#[test]
+fn ecef() {
+    let origin = EcefLocation::from(&GeodeticLocation::new(0.0, 0.0, 0.0).unwrap());
+    assert!((origin.x - 6378137.0).abs() < 1e-6);
+    assert!(origin.y.abs() < 1e-6 && origin.z.abs() < 1e-6);
+
+    let pole = GeodeticLocation::from(&EcefLocation::new(0.0, 0.0, 6356752.314245));
+    assert!((pole.latitude.to_degrees() - 90.0).abs() < 1e-12);
+    assert!(pole.height.abs() < 1e-6);
+
+    for (lon, lat, height) in [
+        (102.0, 24.0, 1900.0),
+        (-75.5, -89.9, -400.0),
+        (200.0, 45.0, 400e3),
+        (10.0, 0.0, 35786e3),
+        (0.0, 90.0, 0.0),
+    ] {
+        let l = GeodeticLocation::new(lon, lat, height).unwrap();
+        let back = GeodeticLocation::from(&EcefLocation::from(&l));
+        assert!((back.latitude - l.latitude).abs() < 1e-12);
+        assert!((back.height - l.height).abs() < 1e-6);
+        if lat < 90.0 {
+            let dl = (back.longitude - l.longitude)
+                .to_degrees()
+                .rem_euclid(360.0);
+            assert!(!(1e-9..=360.0 - 1e-9).contains(&dl));
+        }
+
+        let c = GeocentricLocation::from(&l);
+        let e = EcefLocation::from(&c);
+        let c_back = GeocentricLocation::from(&e);
+        assert!((c_back.latitude - c.latitude).abs() < 1e-12);
+        assert!((c_back.radius - c.radius).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn gmst() {
+    let j2000 = DateTime::new(2000, 1, 1, 12, 0, 0).unwrap();
+    assert_eq!(j2000.julian_date(), 2451545.0);
+    assert!((j2000.gmst().to_degrees() - 280.46061837).abs() < 1e-9);
+
+    let t = DateTime::new(1992, 8, 20, 12, 14, 0).unwrap();
+    assert!((t.julian_date() - 2448855.009722).abs() < 1e-6);
+    assert!((t.gmst().to_degrees() - 152.578787810).abs() < 1e-3);
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn wmm_ecef() {
+    let wmm = WMM::new(2025.5).unwrap();
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let m = wmm.at_location(&l);
+
+    let ecef = EcefLocation::from(&l);
+    let b = wmm.at_ecef(&ecef);
+
+    let (sin_p, cos_p) = (l.latitude.sin(), l.latitude.cos());
+    let (sin_l, cos_l) = (l.longitude.sin(), l.longitude.cos());
+    let horizontal = -m.x * sin_p - m.z * cos_p;
+    assert!((b.x - (horizontal * cos_l - m.y * sin_l)).abs() < 1e-6);
+    assert!((b.y - (horizontal * sin_l + m.y * cos_l)).abs() < 1e-6);
+    assert!((b.z - (m.x * cos_p - m.z * sin_p)).abs() < 1e-6);
+
+    let f = (b.x.powi(2) + b.y.powi(2) + b.z.powi(2)).sqrt();
+    assert!((f - m.f).abs() < 1e-6);
+    let f_dot = (b.x * b.x_dot + b.y * b.y_dot + b.z * b.z_dot) / f;
+    assert!((f_dot - m.f_dot).abs() < 1e-9);
+
+    let gmst = DateTime::new(2025, 7, 2, 6, 30, 0).unwrap().gmst();
+    let eci = ecef.to_eci(gmst);
+    let b_eci = wmm.at_eci(&eci, gmst);
+    let b_back = b_eci.to_ecef(gmst);
+    assert!((b_back.x - b.x).abs() < 1e-6);
+    assert!((b_back.y - b.y).abs() < 1e-6);
+    assert!((b_eci.z - b.z).abs() < 1e-6);
+    assert!((eci.to_ecef(gmst).x - ecef.x).abs() < 1e-6);
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn gradient() {
+    let wmm = WMM::new(2026.5).unwrap();
+
+    for (lon, lat, height) in [
+        (102.0, 24.0, 1900.0),
+        (-60.0, -55.0, 400e3),
+        (30.0, 89.9, 0.0),
+        (0.0, 90.0, 0.0),
+    ] {
+        let l = GeodeticLocation::new(lon, lat, height).unwrap();
+        let g = wmm.gradient_at_location(&l);
+
+        let (sin_p, cos_p) = (l.latitude.sin(), l.latitude.cos());
+        let (sin_l, cos_l) = (l.longitude.sin(), l.longitude.cos());
+        let ned = [
+            [-sin_p * cos_l, -sin_p * sin_l, cos_p],
+            [-sin_l, cos_l, 0.0],
+            [-cos_p * cos_l, -cos_p * sin_l, -sin_p],
+        ];
+
+        let p = EcefLocation::from(&l);
+        let step = 10.0;
+        for (j, e) in ned.iter().enumerate() {
+            let at = |s: f64| {
+                let b = wmm.at_ecef(&EcefLocation::new(
+                    p.x + s * e[0],
+                    p.y + s * e[1],
+                    p.z + s * e[2],
+                ));
+                [b.x, b.y, b.z]
+            };
+            let (b1, b0) = (at(step), at(-step));
+
+            for (i, n) in ned.iter().enumerate() {
+                let db: f64 = (0..3).map(|k| n[k] * (b1[k] - b0[k])).sum();
+                let expected = db / (2.0 * step);
+                assert!(
+                    (g.tensor[i][j] - expected).abs() < 1e-6,
+                    "{lat} {i} {j}: {} {}",
+                    g.tensor[i][j],
+                    expected
+                );
+            }
+        }
+
+        let trace = g.tensor[0][0] + g.tensor[1][1] + g.tensor[2][2];
+        assert!(trace.abs() < 1e-12);
+        for i in 0..3 {
+            for j in 0..3 {
+                assert!((g.tensor[i][j] - g.tensor[j][i]).abs() < 1e-12);
+            }
+        }
+
+        let g0 = wmm.at_time(2025.5).unwrap().gradient_at_location(&l);
+        let g1 = wmm.at_time(2027.5).unwrap().gradient_at_location(&l);
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = (g1.tensor[i][j] - g0.tensor[i][j]) / 2.0;
+                assert!((g.tensor_dot[i][j] - expected).abs() < 1e-12);
+            }
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn ellipsoid() {
+    let wmm = WMM::new(2025.5).unwrap();
+
+    let l = GeodeticLocation::with_ellipsoid(102.0, 24.0, 1900.0, Ellipsoid::CLARKE_1866).unwrap();
+    let m = wmm.at_location(&l);
+    let b = wmm.at_ecef(&EcefLocation::from(&l));
+
+    let (sin_p, cos_p) = (l.latitude.sin(), l.latitude.cos());
+    let (sin_l, cos_l) = (l.longitude.sin(), l.longitude.cos());
+    let x = -sin_p * cos_l * b.x - sin_p * sin_l * b.y + cos_p * b.z;
+    let y = -sin_l * b.x + cos_l * b.y;
+    let z = -cos_p * cos_l * b.x - cos_p * sin_l * b.y - sin_p * b.z;
+    assert!((m.x - x).abs() < 1e-6);
+    assert!((m.y - y).abs() < 1e-6);
+    assert!((m.z - z).abs() < 1e-6);
+
+    let wgs84 = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    assert!((wmm.at_location(&wgs84).f - m.f).abs() > 1.0);
+
+    let back = GeodeticLocation::from_ecef(&EcefLocation::from(&l), Ellipsoid::CLARKE_1866);
+    assert!((back.latitude - l.latitude).abs() < 1e-12);
+    assert!((back.height - l.height).abs() < 1e-6);
+
+    let sphere = Ellipsoid::new(6371200.0, 0.0).unwrap();
+    let l = GeodeticLocation::with_ellipsoid(-30.0, 60.0, 1000.0, sphere).unwrap();
+    let m = wmm.at_location(&l);
+    let s = wmm.at_geocentric(&GeocentricLocation::new(-30.0, 60.0, 6372200.0).unwrap());
+    assert!((m.x + s.theta).abs() < 1e-6);
+    assert!((m.y - s.phi).abs() < 1e-6);
+    assert!((m.z + s.r).abs() < 1e-6);
+
+    assert_eq!(Ellipsoid::default(), Ellipsoid::WGS84);
+    assert!((Ellipsoid::GRS80.b() - 6356752.314140).abs() < 1e-5);
+    assert!(Ellipsoid::new(-1.0, 0.0).is_err());
+    assert!(Ellipsoid::new(6378137.0, 1.0).is_err());
+}
+
+#[test]
+#[cfg(feature = "geoid")]
+fn geoid() {
+    let grid = "-90.0 90.0 0.0 360.0 90.0 180.0
+        -10.0 -10.0 -10.0
+        20.0 40.0 20.0
+        30.0 30.0
+        30.0
+    ";
+    let geoid: Geoid = grid.parse().unwrap();
+
+    assert!((geoid.undulation(0.0, 0.0) - 20.0).abs() < 1e-9);
+    assert!((geoid.undulation(180.0, 0.0) - 40.0).abs() < 1e-9);
+    assert!((geoid.undulation(90.0, 0.0) - 30.0).abs() < 1e-9);
+    assert!((geoid.undulation(90.0, 45.0) - 10.0).abs() < 1e-9);
+    assert!((geoid.undulation(-90.0, -45.0) - 30.0).abs() < 1e-9);
+    assert!((geoid.undulation(360.0, 90.0) + 10.0).abs() < 1e-9);
+
+    let l = GeodeticLocation::from_msl_with(90.0, 0.0, 1900.0, &geoid).unwrap();
+    assert!((l.height - 1930.0).abs() < 1e-9);
+    assert!(GeodeticLocation::from_msl_with(90.0, 91.0, 0.0, &geoid).is_err());
+
+    let err = "-90 90 0 360 90 180\n1 2 3\n4 5"
+        .parse::<Geoid>()
+        .unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::MissingField);
+    let err = "-90 90 0 360 7 180\n1 2 3".parse::<Geoid>().unwrap_err();
+    assert_eq!(err.kind(), &ParseErrorKind::InvalidHeader);
+    let err = "-90 90 0 360 90 180\n1 2 x".parse::<Geoid>().unwrap_err();
+    assert_eq!(
+        (err.line(), err.kind()),
+        (2, &ParseErrorKind::InvalidNumber)
+    );
+
+    let regional: Geoid = "40 50 0 20 10 10\n1 2 3\n4 5 6".parse().unwrap();
+    assert!((regional.undulation(10.0, 45.0) - 3.5).abs() < 1e-9);
+    assert!((regional.undulation(-10.0, 50.0) - 1.0).abs() < 1e-9);
+    assert!((regional.undulation(350.0, 40.0) - 4.0).abs() < 1e-9);
+    assert!((regional.undulation(30.0, 50.0) - 3.0).abs() < 1e-9);
+    assert!((regional.undulation(180.0, 40.0) - 6.0).abs() < 1e-9);
+    assert!((regional.undulation(200.0, 40.0) - 4.0).abs() < 1e-9);
+
+    let geoid: Geoid = "0 90 0 360 90 180\n0.005 -0.005 0.005\n-0.015 0.015 -0.015"
+        .parse()
+        .unwrap();
+    assert_eq!(geoid.undulation(0.0, 90.0), 0.01);
+    assert_eq!(geoid.undulation(180.0, 90.0), -0.01);
+    assert_eq!(geoid.undulation(180.0, 0.0), 0.02);
+
+    let egm96 = Geoid::egm96();
+    assert!((egm96.undulation(0.0, 0.0) - 17.16).abs() < 0.5);
+    assert!((egm96.undulation(78.75, 4.75) + 106.2).abs() < 2.0);
+    assert!((egm96.undulation(-90.0, 90.0) - 13.61).abs() < 0.5);
+    assert!((egm96.undulation(0.0, -90.0) + 29.53).abs() < 0.5);
+
+    let l = GeodeticLocation::from_msl(0.0, 0.0, 1900.0).unwrap();
+    assert!((l.height - 1917.16).abs() < 0.5);
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn wmm_uncertainty() {
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+
+    let m = WMM::new(2026.0).unwrap().at_location(&l);
+    let u = m.uncertainty.unwrap();
+    assert_eq!(
+        (u.x, u.y, u.z, u.h, u.f),
+        (137.0, 89.0, 141.0, 133.0, 138.0)
+    );
+    assert!((u.i.to_degrees() - 0.20).abs() < 1e-12);
+    let d = (0.26_f64.powi(2) + (5417.0 / m.h).powi(2)).sqrt();
+    assert!((u.d.to_degrees() - d).abs() < 1e-12);
+
+    let m = WMM::release(WmmRelease::Wmm2020, 2024.0)
+        .unwrap()
+        .at_location(&l);
+    let u = m.uncertainty.unwrap();
+    assert_eq!(
+        (u.x, u.y, u.z, u.h, u.f),
+        (131.0, 94.0, 157.0, 128.0, 148.0)
+    );
+    let d = (0.26_f64.powi(2) + (5625.0 / m.h).powi(2)).sqrt();
+    assert!((u.d.to_degrees() - d).abs() < 1e-12);
+
+    let u = WmmRelease::Wmm2025.uncertainty(0.0);
+    assert!(u.d.is_infinite());
+
+    let content = std::fs::read_to_string("data/WMM2025.COF").unwrap();
+    let cof: Cof = format!(
+        "2025.0 WMM2025 11/13/2024\n{}999999999999999999999999999999999999999999999999\n",
+        content
+    )
+    .parse()
+    .unwrap();
+    assert!(WMM::from_cof(&cof, 2026.0)
+        .unwrap()
+        .at_location(&l)
+        .uncertainty
+        .is_none());
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn zones() {
+    assert_eq!(Zone::from_h(1999.0), Zone::Blackout);
+    assert_eq!(Zone::from_h(2000.0), Zone::Caution);
+    assert_eq!(Zone::from_h(5999.0), Zone::Caution);
+    assert_eq!(Zone::from_h(6000.0), Zone::Normal);
+
+    let wmm = WMM::new(2025.0).unwrap();
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    assert_eq!(wmm.at_location(&l).zone, Zone::Normal);
+
+    for (hemisphere, lat, lon) in [
+        (Hemisphere::North, 85.8, 139.3),
+        (Hemisphere::South, -63.8, 135.9),
+    ] {
+        let zones = wmm.zone_boundaries(hemisphere, 0.0, 36).unwrap();
+        let pole = zones.pole;
+        assert!((pole.latitude.to_degrees() - lat).abs() < 0.2);
+        assert!((pole.longitude.to_degrees() - lon).abs() < 1.0);
+
+        let m = wmm.at_location(&pole);
+        assert!(m.h < 1.0);
+        assert_eq!(m.zone, Zone::Blackout);
+
+        assert_eq!(zones.blackout.len(), 36);
+        assert_eq!(zones.caution.len(), 36);
+        for l in &zones.blackout {
+            assert!((wmm.at_location(&l.unwrap()).h - 2000.0).abs() < 1e-3);
+        }
+        for l in &zones.caution {
+            assert!((wmm.at_location(&l.unwrap()).h - 6000.0).abs() < 1e-3);
+        }
+    }
+
+    // Far enough out, h stays below the caution threshold on every bearing.
+    let zones = wmm.zone_boundaries(Hemisphere::North, 2e7, 8).unwrap();
+    assert_eq!(zones.caution.len(), 8);
+    assert!(zones.caution.iter().all(Option::is_none));
+    assert!(wmm.zone_boundaries(Hemisphere::North, f64::NAN, 8).is_err());
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn grid_variation() {
+    use geomag::navigation::{grid_variation, Angle, TransverseMercator};
+
+    let wmm = WMM::new(2025.5).unwrap();
+
+    let l = GeodeticLocation::new(30.0, 70.0, 0.0).unwrap();
+    let m = wmm.at_location(&l);
+    let gv = grid_variation(&m, &l);
+    assert!((gv.value - (m.d - 30_f64.to_radians())).abs() < 1e-12);
+    assert_eq!(gv.rate, m.d_dot);
+
+    let l = GeodeticLocation::new(-100.0, -60.0, 0.0).unwrap();
+    let m = wmm.at_location(&l);
+    let gv = grid_variation(&m, &l);
+    let expected = m.d - 100_f64.to_radians();
+    let expected = expected.sin().atan2(expected.cos());
+    assert!((gv.value - expected).abs() < 1e-12);
+
+    let l = GeodeticLocation::new(300.0, 80.0, 0.0).unwrap();
+    let m = wmm.at_location(&l);
+    let Angle { value, .. } = grid_variation(&m, &l);
+    assert!((-std::f64::consts::PI..=std::f64::consts::PI).contains(&value));
+    assert!((value - (m.d + 60_f64.to_radians())).abs() < 1e-12);
+
+    // UTM zone 48 has its central meridian at 105° E, so the convergence here is close to
+    // atan(tan(-3°) sin(24°)), about -1.22°.
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let m = wmm.at_location(&l);
+    let gv = grid_variation(&m, &l);
+    let convergence = ((-3_f64).to_radians().tan() * 24_f64.to_radians().sin()).atan();
+    assert!((gv.value - (m.d - convergence)).abs() < 1e-4);
+    assert!((gv.value - (m.d - TransverseMercator::utm(&l).convergence(&l))).abs() < 1e-12);
+    assert_eq!(gv.rate, m.d_dot);
+
+    let l = GeodeticLocation::new(105.0, -30.0, 0.0).unwrap();
+    let m = wmm.at_location(&l);
+    assert!((grid_variation(&m, &l).value - m.d).abs() < 1e-12);
+}
+
+#[test]
+fn utm_convergence() {
+    use geomag::navigation::{utm_zone, TransverseMercator};
+
+    let zone = |lon: f64, lat: f64| utm_zone(&GeodeticLocation::new(lon, lat, 0.0).unwrap());
+    assert_eq!(zone(102.0, 24.0), 48);
+    assert_eq!(zone(-180.0, 0.0), 1);
+    assert_eq!(zone(179.9, 0.0), 60);
+    assert_eq!(zone(350.0, 0.0), 29);
+    assert_eq!(zone(5.0, 60.0), 32);
+    assert_eq!(zone(10.0, 78.0), 33);
+    assert_eq!(zone(40.0, 80.0), 37);
+
+    let l = GeodeticLocation::new(102.0, 24.0, 0.0).unwrap();
+    let tm = TransverseMercator::utm(&l);
+    assert_eq!(tm.central_meridian(), 105.0);
+
+    let on_meridian = GeodeticLocation::new(105.0, 24.0, 0.0).unwrap();
+    assert!(tm.convergence(&on_meridian).abs() < 1e-15);
+
+    // Series of Snyder (1987), accurate to well below 1e-8 rad within a few degrees.
+    let e_2 = Ellipsoid::WGS84.e_2();
+    for (lon, lat) in [(102.0, 24.0), (108.0, 40.0), (101.0, -60.0), (104.0, 0.0)] {
+        let l = GeodeticLocation::new(lon, lat, 0.0).unwrap();
+        let lambda = (lon - 105.0_f64).to_radians();
+        let (sin, cos, tan) = (l.latitude.sin(), l.latitude.cos(), l.latitude.tan());
+        let eta_2 = e_2 / (1.0 - e_2) * cos.powi(2);
+        let expected = lambda
+            * sin
+            * (1.0
+                + lambda.powi(2) * cos.powi(2) / 3.0 * (1.0 + 3.0 * eta_2 + 2.0 * eta_2.powi(2))
+                + lambda.powi(4) * cos.powi(4) / 15.0 * (2.0 - tan.powi(2)));
+        assert!((tm.convergence(&l) - expected).abs() < 1e-8);
+    }
+
+    let pole = GeodeticLocation::new(120.0, 90.0, 0.0).unwrap();
+    assert!((tm.convergence(&pole) - 15_f64.to_radians()).abs() < 1e-9);
+    let pole = GeodeticLocation::new(120.0, -90.0, 0.0).unwrap();
+    assert!((tm.convergence(&pole) + 15_f64.to_radians()).abs() < 1e-9);
+}
+
+#[test]
+#[cfg(feature = "wmm")]
+fn bearings() {
+    use geomag::navigation::{Correction, North, TransverseMercator};
+    use std::f64::consts::TAU;
+
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let m = WMM::new(2025.5).unwrap().at_location(&l);
+    let gamma = TransverseMercator::utm(&l).convergence(&l);
+    let c = Correction::new(&m, gamma);
+
+    let total = c.total();
+    assert!((total.value - (m.d - gamma)).abs() < 1e-12);
+    assert_eq!(total.rate, m.d_dot);
+
+    let b = 1.0;
+    let t = c.convert(b, North::Magnetic, North::True);
+    assert!((t.value - (b + m.d)).abs() < 1e-12);
+    assert_eq!(t.rate, m.d_dot);
+
+    let g = c.convert(b, North::Magnetic, North::Grid);
+    assert!((g.value - (b + m.d - gamma)).abs() < 1e-12);
+    assert_eq!(g.rate, m.d_dot);
+
+    let back = c.convert(g.value, North::Grid, North::Magnetic);
+    assert!((back.value - b).abs() < 1e-12);
+    assert_eq!(back.rate, -m.d_dot);
+
+    let wrapped = c.convert(0.0, North::True, North::Grid);
+    assert!((0.0..TAU).contains(&wrapped.value));
+    assert!((wrapped.value - (-gamma).rem_euclid(TAU)).abs() < 1e-12);
+    assert_eq!(c.convert(2.0, North::Grid, North::Grid).value, 2.0);
+}
+
+#[test]
+#[cfg(all(feature = "igrf", feature = "wmm"))]
+fn cli_point() {
+    use std::process::Command;
+
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_geomag"))
+            .args(args)
+            .output()
+            .unwrap()
+    };
+
+    let out = run(&[
+        "--lat",
+        "24",
+        "--lon",
+        "102",
+        "--height",
+        "1.9",
+        "--date",
+        "2023-11-01",
+        "--format",
+        "csv",
+    ]);
+    assert!(out.status.success());
+    let out = String::from_utf8(out.stdout).unwrap();
+    let row: Vec<&str> = out.lines().nth(1).unwrap().split(',').collect();
+
+    let l = GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap();
+    let m = WMM::new(2023.8328767123287).unwrap().at_location(&l);
+    assert_eq!(row[0], "WMM2020");
+    assert!((row[5].parse::<f64>().unwrap() - m.x).abs() < 1e-6);
+    assert!((row[17].parse::<f64>().unwrap() - m.d.to_degrees()).abs() < 1e-9);
+
+    let out = run(&[
+        "point", "--lat", "80N", "--lon", "100W", "--date", "1990.5", "--model", "igrf",
+        "--format", "json",
+    ]);
+    let out = String::from_utf8(out.stdout).unwrap();
+    assert!(
+        out.starts_with("{\"model\":\"IGRF14\",\"date\":1990.5,\"latitude\":80,\"longitude\":-100")
+    );
+
+    let out = run(&[
+        "--lat", "24.5", "--lon", "102", "--height", "1900m", "--msl", "--date", "2025.5",
+        "--format", "csv",
+    ]);
+    #[cfg(feature = "geoid")]
+    {
+        assert!(out.status.success());
+        let out = String::from_utf8(out.stdout).unwrap();
+        let row: Vec<&str> = out.lines().nth(1).unwrap().split(',').collect();
+        let l = GeodeticLocation::from_msl(102.0, 24.5, 1900.0).unwrap();
+        let m = WMM::new(2025.5).unwrap().at_location(&l);
+        assert!((row[5].parse::<f64>().unwrap() - m.x).abs() < 1e-6);
+    }
+    #[cfg(not(feature = "geoid"))]
+    {
+        assert_eq!(out.status.code(), Some(2));
+        let err = String::from_utf8(out.stderr).unwrap();
+        assert!(err.contains("heights above mean sea level need the geoid feature"));
+    }
+
+    let out = run(&["--lat", "24", "--lon", "102", "--date", "2031.0"]);
+    assert_eq!(out.status.code(), Some(1));
+
+    let out = run(&["--lat", "24"]);
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+#[cfg(all(feature = "igrf", feature = "wmm"))]
+fn cli_grid() {
+    use std::process::Command;
+
+    let out = Command::new(env!("CARGO_BIN_EXE_geomag"))
+        .args([
+            "grid",
+            "--lat",
+            "-10:10:10",
+            "--lon",
+            "0:20:5",
+            "--height",
+            "0:2:1",
+        ])
+        .args(["--date", "2024:2026:0.5", "--components", "x,d,d_dot"])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+
+    let out = String::from_utf8(out.stdout).unwrap();
+    let mut lines = out.lines();
+    assert_eq!(
+        lines.next(),
+        Some("date,latitude,longitude,height,x,d,d_dot")
+    );
+    let rows: Vec<Vec<f64>> = lines
+        .map(|l| l.split(',').map(|v| v.parse().unwrap()).collect())
+        .collect();
+    assert_eq!(rows.len(), 5 * 3 * 3 * 5);
+
+    // WMM2020 until its end, then WMM2025.
+    for row in [&rows[0], &rows[100], &rows[224]] {
+        let l = GeodeticLocation::new(row[2], row[1], row[3]).unwrap();
+        let m = WMM::new(row[0]).unwrap().at_location(&l);
+        assert!((row[4] - m.x).abs() < 1e-6);
+        assert!((row[5] - m.d.to_degrees()).abs() < 1e-9);
+        assert!((row[6] - m.d_dot.to_degrees()).abs() < 1e-9);
+    }
+    assert_eq!(rows[224][..4], [2026.0, 10.0, 20.0, 2000.0]);
+}
+
+#[test]
+#[cfg(all(feature = "igrf", feature = "wmm"))]
+fn cli_file() {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let run = |args: &[&str], input: &str| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_geomag"))
+            .arg("file")
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .unwrap();
+        let out = child.wait_with_output().unwrap();
+        let stdout = String::from_utf8(out.stdout).unwrap();
+        let stderr = String::from_utf8(out.stderr).unwrap();
+        (out.status.code(), stdout, stderr)
+    };
+
+    let legacy =
+        "2023,11,1 D E1.9 24 102\n2031.0 D E0 10 20\n2025.5 D E0 95 20\n2024.0 C K6371.2 0 0\n";
+    let (code, stdout, stderr) = run(&[], legacy);
+    assert_eq!(code, Some(1));
+    assert!(stderr.contains("line 2: WMM is not valid at 2031"));
+    assert!(stderr.contains("line 3: invalid latitude: 95"));
+    assert!(stderr.contains("2 of 4 records failed"));
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("# date coord-system altitude latitude longitude x x_dot"));
+    let values: Vec<f64> = lines[1]
+        .split(' ')
+        .skip(5)
+        .map(|v| v.parse().unwrap())
+        .collect();
+    let m = WMM::new(2023.8328767123287)
+        .unwrap()
+        .at_location(&GeodeticLocation::new(102.0, 24.0, 1900.0).unwrap());
+    assert!((values[0] - m.x).abs() < 0.05);
+    assert!((values[12] - m.d.to_degrees()).abs() < 5e-5);
+    assert!(lines[2].ends_with("ERROR: WMM is not valid at 2031, expected 2020 to 2030"));
+
+    // The geocentric record sits on the reference sphere at the equator.
+    let l = GeodeticLocation::from(&EcefLocation::new(6371200.0, 0.0, 0.0));
+    let m = WMM::new(2024.0).unwrap().at_location(&l);
+    let values: Vec<f64> = lines[4]
+        .split(' ')
+        .skip(5)
+        .map(|v| v.parse().unwrap())
+        .collect();
+    assert!((values[4] - m.z).abs() < 0.05);
+
+    let (code, stdout, stderr) = run(&[], "2025.5 D K1.9 24 102\n");
+    #[cfg(feature = "geoid")]
+    {
+        assert_eq!(code, Some(0));
+        assert!(stderr.is_empty());
+        let l = GeodeticLocation::from_msl(102.0, 24.0, 1900.0).unwrap();
+        let m = WMM::new(2025.5).unwrap().at_location(&l);
+        let values: Vec<f64> = stdout
+            .lines()
+            .nth(1)
+            .unwrap()
+            .split(' ')
+            .skip(5)
+            .map(|v| v.parse().unwrap())
+            .collect();
+        assert!((values[4] - m.z).abs() < 0.05);
+    }
+    #[cfg(not(feature = "geoid"))]
+    {
+        assert_eq!(code, Some(1));
+        assert!(stderr.contains("line 1: heights above mean sea level need the geoid feature"));
+        assert!(stdout.ends_with("ERROR: heights above mean sea level need the geoid feature\n"));
+    }
+
+    let csv = "id,when,lat,lon\n1,2022.5,10S,20W\n2,2040.0,0,0\n\"Gate 3, \"\"north\"\"\", 2022.5 ,\"45.5\",0\n";
+    let args = [
+        "--input-format",
+        "csv",
+        "--model",
+        "igrf",
+        "--date-column",
+        "when",
+    ];
+    let (code, stdout, _) = run(
+        &[&args[..], &["--lat-column", "lat", "--lon-column", "lon"]].concat(),
+        csv,
+    );
+    assert_eq!(code, Some(1));
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines[0],
+        "id,when,lat,lon,x,x_dot,y,y_dot,z,z_dot,h,h_dot,f,f_dot,i,i_dot,d,d_dot,error"
+    );
+    let row: Vec<&str> = lines[1].split(',').collect();
+    let m = IGRF::new(2022.5)
+        .unwrap()
+        .at_location(&GeodeticLocation::new(-20.0, -10.0, 0.0).unwrap());
+    assert!((row[4].parse::<f64>().unwrap() - m.x).abs() < 1e-6);
+    assert_eq!(row[18], "");
+    assert!(lines[2].ends_with(",,\"IGRF is not valid at 2040, expected 1900 to 2030\""));
+
+    assert!(lines[3].starts_with("\"Gate 3, \"\"north\"\"\", 2022.5 ,\"45.5\",0,"));
+    let m = IGRF::new(2022.5)
+        .unwrap()
+        .at_location(&GeodeticLocation::new(0.0, 45.5, 0.0).unwrap());
+    let x = lines[3].split(',').nth(5).unwrap();
+    assert!((x.parse::<f64>().unwrap() - m.x).abs() < 1e-6);
+    assert!(lines[3].ends_with(','));
+}
+
+#[test]
+#[cfg(all(feature = "igrf", feature = "wmm", feature = "server"))]
+fn server() {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpStream;
+    use std::process::{Child, Command, Stdio};
+
+    struct Server(Child);
+
+    impl Drop for Server {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    let mut server = Server(
+        Command::new(env!("CARGO_BIN_EXE_geomag-server"))
+            .args(["--bind", "127.0.0.1:0"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap(),
+    );
+
+    let mut line = String::new();
+    BufReader::new(server.0.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let address = line
+        .trim()
+        .trim_start_matches("Listening on http://")
+        .to_string();
+
+    let send = |request: &str| {
+        let mut stream = TcpStream::connect(&address).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.to_string(), body.to_string())
+    };
+    let get = |target: &str| {
+        send(&format!(
+            "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            target
+        ))
+    };
+
+    #[cfg(feature = "geoid")]
+    let l = GeodeticLocation::from_msl(-105.25, 40.0, 0.0).unwrap();
+    #[cfg(not(feature = "geoid"))]
+    let l = GeodeticLocation::new(-105.25, 40.0, 0.0).unwrap();
+    let m = WMM::new(2025.0).unwrap().at_location(&l);
+
+    let path = "/geomag-web/calculators/calculateDeclination";
+    let (head, body) = get(&format!(
+        "{}?lat1=40&lat1Hemisphere=N&lon1=105.25&lon1Hemisphere=W&startYear=2025&key=x&resultFormat=json",
+        path
+    ));
+    assert!(head.starts_with("HTTP/1.1 200 OK"));
+    assert!(head.contains("Content-Type: application/json"));
+    assert!(body.contains(&format!("\"declination\": {:.5},", m.d.to_degrees())));
+    assert!(body.contains("\"model\": \"WMM2025\""));
+
+    let (_, body) = get(&format!(
+        "{}?lat1=40&lon1=-105.25&startYear=2025&resultFormat=xml",
+        path
+    ));
+    assert!(body.contains(&format!(
+        "<declination units=\"Degree\">{:.5}</declination>",
+        m.d.to_degrees()
+    )));
+
+    let (_, body) = get("/calculateIgrfwmm?lat1=40&lon1=-105.25&model=IGRF&startYear=2020&endYear=2022&resultFormat=csv");
+    let rows: Vec<&str> = body.lines().filter(|l| !l.starts_with('#')).collect();
+    assert_eq!(rows.len(), 3);
+    let m = IGRF::new(2021.0).unwrap().at_location(&l);
+    assert!(rows[1].starts_with("2021.00000,0.00000,40.00000,-105.25000,"));
+    assert!(rows[1].contains(&format!(",{:.5},", m.x)));
+
+    let (head, body) = get(&format!("{}?lat1=95&lon1=0", path));
+    assert!(head.starts_with("HTTP/1.1 400"));
+    assert_eq!(body, "invalid latitude: 95\n");
+
+    #[cfg(feature = "geoid")]
+    let datum = "mean sea level";
+    #[cfg(not(feature = "geoid"))]
+    let datum = "WGS84 ellipsoid";
+    let (_, body) = get(&format!("{}?lat1=40&lon1=0&elevation=1", path));
+    assert!(body.contains(&format!("\"elevation_datum\": \"{}\"", datum)));
+
+    let (head, body) = get(&format!(
+        "{}?lat1=40&lon1=0&startYear=2025&endYear=2029&dateStepSize=0.0001",
+        path
+    ));
+    assert!(head.starts_with("HTTP/1.1 400"));
+    assert_eq!(body, "2025 to 2029 by 0.0001 gives more than 10000 dates\n");
+    let (head, body) = get(&format!(
+        "{}?lat1=40&lon1=0&startYear=2025&endYear=2020",
+        path
+    ));
+    assert!(head.starts_with("HTTP/1.1 400"));
+    assert_eq!(body, "invalid span: 2025 to 2020 by 1\n");
+
+    let (head, body) = get(&format!("{}?key={}", path, "x".repeat(10_000)));
+    assert!(head.starts_with("HTTP/1.1 400"));
+    assert_eq!(body, "request line too long\n");
+    let header = format!("X-Padding: {}\r\n", "x".repeat(1000));
+    let (head, _) = send(&format!(
+        "GET {}?lat1=40&lon1=0 HTTP/1.1\r\n{}\r\n",
+        path,
+        header.repeat(40)
+    ));
+    assert!(head.starts_with("HTTP/1.1 431"));
+    let (head, _) = send(&format!(
+        "GET {}?lat1=40&lon1=0 HTTP/1.1\r\n{}\r\n",
+        path,
+        header.repeat(20)
+    ));
+    assert!(head.starts_with("HTTP/1.1 200"));
 }